@@ -0,0 +1,932 @@
+//! This module provides the layered configuration for the scraper service.
+//!
+//! Settings are resolved in the following order, each layer overriding the previous one:
+//! 1. Built-in defaults (matching the values the scraper was originally hardcoded with).
+//! 2. A TOML file (`deepness.toml` by default, or the path given by `--config` / `DEEPNESS_CONFIG`).
+//! 3. Environment variables prefixed with `DEEPNESS_`.
+//! 4. Command line flags.
+//!
+//! The resolved `Config` is validated once at startup so that a bad deployment fails fast
+//! instead of in the middle of a scraping session.
+
+use crate::processing::relevance::RelevanceLabel;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::Parser;
+use serde::Deserialize;
+use std::{
+    env,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+/// The configuration file looked up when no explicit path is given.
+const DEFAULT_CONFIG_PATH: &str = "deepness.toml";
+
+/// Prefix shared by every environment variable override.
+const ENV_PREFIX: &str = "DEEPNESS_";
+
+/// The fully resolved application configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Settings for launching the Chrome instance.
+    pub browser: BrowserConfig,
+    /// Settings controlling the scrolling and collection loop.
+    pub scraping: ScrapingConfig,
+    /// Settings for the login flow and the bot account credentials.
+    pub login: LoginConfig,
+    /// Settings for the MongoDB database shared with the Express server and the AI service.
+    pub database: DatabaseConfig,
+    /// Settings for picking profiles from the watchlist.
+    pub watchlist: WatchlistConfig,
+    /// Settings for the concurrent scraper pool.
+    pub pool: PoolConfig,
+    /// Settings for historical backfill runs.
+    pub backfill: BackfillConfig,
+    /// Settings for resolving `t.co` shortlinks.
+    pub links: LinksConfig,
+    /// Settings for the rule-based relevance scoring of processed records.
+    pub relevance: RelevanceConfig,
+    /// Settings for classifying records by chain and ecosystem.
+    pub chains: ChainsConfig,
+    /// Settings for flagging phishing and drainer links.
+    pub link_safety: LinkSafetyConfig,
+    /// Settings for grouping near-duplicate tweets into clusters.
+    pub clustering: ClusteringConfig,
+}
+
+/// Settings used by `UserProfileScraper::new` to launch the browser.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BrowserConfig {
+    /// Runs Chrome without a visible window. Usually `false` on a dev laptop, `true` on a server.
+    pub headless: bool,
+    /// Directory holding the persistent Chrome profile and the saved session cookies.
+    pub user_data_dir: PathBuf,
+    /// User agent string passed to Chrome.
+    pub user_agent: String,
+    /// Browser window width in pixels.
+    pub window_width: u32,
+    /// Browser window height in pixels.
+    pub window_height: u32,
+}
+
+impl Default for BrowserConfig {
+    fn default() -> Self {
+        Self {
+            headless: false,
+            user_data_dir: PathBuf::from("./chrome_scraper_profile"),
+            user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36".to_string(),
+            window_width: 1920,
+            window_height: 1080,
+        }
+    }
+}
+
+/// Settings used by `UserProfileScraper::scrape_user_posts` while scrolling a profile.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScrapingConfig {
+    /// Maximum number of tweets collected per profile.
+    pub tweet_limit: usize,
+    /// Number of scroll cycles without new tweets after which scrolling stops.
+    pub max_consecutive_failed_cycles: usize,
+    /// Seconds to wait after a page navigation for dynamic content to render.
+    pub page_load_settle_secs: u64,
+    /// Seconds to wait after a regular scroll.
+    pub settle_after_scroll_secs: u64,
+    /// Seconds to wait after a recovery scroll (a scroll that found nothing new).
+    pub settle_after_recovery_scroll_secs: u64,
+    /// Initial estimate of a tweet's height in pixels, used before any tweet has been measured.
+    pub initial_item_height: f64,
+    /// Stops scrolling at the newest tweet collected by the profile's previous run.
+    pub incremental: bool,
+    /// Captures the GraphQL timeline responses the page loads, which are processed in
+    /// preference to the rendered HTML.
+    pub capture_graphql: bool,
+    /// Opens the status page of tweets that look like the start of a thread and collects the
+    /// author's consecutive self-replies.
+    pub expand_threads: bool,
+    /// Maximum number of threads expanded per profile scrape.
+    pub max_thread_expansions: usize,
+    /// Maximum number of self-replies collected per thread.
+    pub max_thread_length: usize,
+}
+
+impl Default for ScrapingConfig {
+    fn default() -> Self {
+        Self {
+            tweet_limit: 50,
+            max_consecutive_failed_cycles: 3,
+            page_load_settle_secs: 5,
+            settle_after_scroll_secs: 3,
+            settle_after_recovery_scroll_secs: 5,
+            initial_item_height: 250.0,
+            incremental: true,
+            capture_graphql: true,
+            expand_threads: false,
+            max_thread_expansions: 5,
+            max_thread_length: 25,
+        }
+    }
+}
+
+impl ScrapingConfig {
+    /// Returns the wait applied after a page navigation.
+    pub fn page_load_settle(&self) -> Duration {
+        Duration::from_secs(self.page_load_settle_secs)
+    }
+
+    /// Returns the wait applied after a regular scroll.
+    pub fn settle_after_scroll(&self) -> Duration {
+        Duration::from_secs(self.settle_after_scroll_secs)
+    }
+
+    /// Returns the wait applied after a recovery scroll.
+    pub fn settle_after_recovery_scroll(&self) -> Duration {
+        Duration::from_secs(self.settle_after_recovery_scroll_secs)
+    }
+}
+
+/// Settings for the login flow handled by `LoginHandler`.
+///
+/// Credentials are best supplied through the environment (`DEEPNESS_LOGIN_USERNAME`,
+/// `DEEPNESS_LOGIN_PASSWORD`, `DEEPNESS_LOGIN_EMAIL`) rather than committed to a TOML file.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoginConfig {
+    /// Whether the scraper should attempt to log in when the session is not active.
+    pub enabled: bool,
+    /// Bot account username (with or without the leading `@`).
+    pub username: String,
+    /// Bot account password.
+    pub password: String,
+    /// Email or phone used for the intermediate verification step.
+    pub email: String,
+}
+
+impl Default for LoginConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            username: String::new(),
+            password: String::new(),
+            email: String::new(),
+        }
+    }
+}
+
+// Written by hand so that credentials never end up in logs.
+impl std::fmt::Debug for LoginConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoginConfig")
+            .field("enabled", &self.enabled)
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .field("email", &self.email)
+            .finish()
+    }
+}
+
+/// Settings for the MongoDB connection used by the repositories in `db`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// MongoDB connection string. Falls back to `MONGODB_URI`, shared with the Express server.
+    pub uri: String,
+    /// Database name. The Express server and the AI service both use `test`.
+    pub name: String,
+    /// Collection holding `Airdrop` records (the `AirdropModel` collection).
+    pub airdrops_collection: String,
+    /// Collection holding token/project entities.
+    pub tokens_collection: String,
+    /// Collection holding the watchlist of profiles to scrape.
+    pub profiles_collection: String,
+    /// Collection holding the clusters of near-duplicate tweets.
+    pub clusters_collection: String,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            uri: "mongodb://localhost:27017".to_string(),
+            name: "test".to_string(),
+            airdrops_collection: "airdrops".to_string(),
+            tokens_collection: "tokens".to_string(),
+            profiles_collection: "profiles".to_string(),
+            clusters_collection: "clusters".to_string(),
+        }
+    }
+}
+
+/// Settings used by `ProfileRepository::due_profiles` to pick the profiles of a run.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchlistConfig {
+    /// Minutes between two scrapes of a profile that does not set its own `intervalMinutes`.
+    pub default_interval_minutes: u32,
+    /// Maximum number of profiles scraped in one run.
+    pub max_profiles_per_run: usize,
+    /// Minutes a profile is skipped after a failed scrape. The wait doubles with every further
+    /// consecutive failure.
+    pub failure_backoff_minutes: u32,
+    /// Upper bound of the wait after consecutive failures.
+    pub max_failure_backoff_minutes: u32,
+}
+
+impl Default for WatchlistConfig {
+    fn default() -> Self {
+        Self {
+            default_interval_minutes: 60,
+            max_profiles_per_run: 100,
+            failure_backoff_minutes: 30,
+            max_failure_backoff_minutes: 24 * 60,
+        }
+    }
+}
+
+/// Settings used by `ScraperPool` to distribute profiles across workers.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PoolConfig {
+    /// Number of concurrent workers, each with its own browser and session.
+    pub workers: usize,
+    /// Maximum number of attempts per profile, retries included.
+    pub max_attempts: u32,
+    /// Seconds a profile waits before being retried after a transient failure.
+    pub retry_backoff_secs: u64,
+    /// Seconds every worker pauses after a rate limit that does not say how long to wait.
+    pub rate_limit_cooldown_secs: u64,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            workers: 1,
+            max_attempts: 2,
+            retry_backoff_secs: 30,
+            rate_limit_cooldown_secs: 900,
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Returns the wait before a transient failure is retried.
+    pub fn retry_backoff(&self) -> Duration {
+        Duration::from_secs(self.retry_backoff_secs)
+    }
+
+    /// Returns the pause of every worker after a rate limit without a `retry_after`.
+    pub fn rate_limit_cooldown(&self) -> Duration {
+        Duration::from_secs(self.rate_limit_cooldown_secs)
+    }
+}
+
+/// Settings for backfill runs, which scroll each profile back to a date instead of stopping
+/// at the tweet limit or at the previous run's watermark.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackfillConfig {
+    /// Enables backfill mode: every active profile is scrolled back to this date (UTC,
+    /// inclusive), e.g. `"2026-07-01"`. `None` runs regular incremental scrapes.
+    pub until: Option<NaiveDate>,
+    /// Maximum number of tweets collected per profile in backfill mode.
+    pub max_tweets: usize,
+    /// Directory holding the checkpoints of unfinished backfills.
+    pub checkpoint_dir: PathBuf,
+    /// Number of newly collected tweets after which the checkpoint is rewritten.
+    pub checkpoint_every: usize,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            until: None,
+            max_tweets: 3200,
+            checkpoint_dir: PathBuf::from("./backfill_checkpoints"),
+            checkpoint_every: 50,
+        }
+    }
+}
+
+impl BackfillConfig {
+    /// Returns the start of the `until` day, the oldest time a backfill collects.
+    pub fn until_time(&self) -> Option<DateTime<Utc>> {
+        self.until.map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+    }
+}
+
+/// Settings for `LinkResolver`, which expands the `t.co` shortlinks the tweet HTML does not
+/// already expand.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LinksConfig {
+    /// Follows the redirects of unexpanded shortlinks with HTTP `HEAD` requests.
+    pub resolve: bool,
+    /// JSON file caching the resolved shortlinks across runs.
+    pub cache_path: PathBuf,
+    /// Seconds before a resolution request is abandoned.
+    pub timeout_secs: u64,
+    /// Maximum number of redirects followed per shortlink.
+    pub max_redirects: u32,
+}
+
+impl Default for LinksConfig {
+    fn default() -> Self {
+        Self {
+            resolve: true,
+            cache_path: PathBuf::from("./link_cache.json"),
+            timeout_secs: 5,
+            max_redirects: 5,
+        }
+    }
+}
+
+impl LinksConfig {
+    /// Returns the timeout of a resolution request.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+}
+
+/// Settings for `RelevanceScorer`, which scores every processed record against a set of rules
+/// before it is stored, so tweets unrelated to airdrops never reach the AI stage.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RelevanceConfig {
+    /// Scores and labels the processed records.
+    pub enabled: bool,
+    /// Drops the records scoring below `min_score` instead of storing them.
+    pub filter: bool,
+    /// Score a record needs to be labeled as relevant (and, with `filter`, to be stored).
+    pub min_score: i32,
+    /// The scoring rules. Rules given in the TOML file replace the built-in ones.
+    pub rules: Vec<RelevanceRule>,
+}
+
+impl Default for RelevanceConfig {
+    fn default() -> Self {
+        let words = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+        Self {
+            enabled: true,
+            filter: true,
+            min_score: 3,
+            rules: vec![
+                RelevanceRule {
+                    name: "airdrop-terms".to_string(),
+                    label: Some(RelevanceLabel::Airdrop),
+                    weight: 3,
+                    keywords: words(&["airdrop", "claim", "eligib", "snapshot", "retroactive", "allocation", "tge"]),
+                    ..RelevanceRule::default()
+                },
+                RelevanceRule {
+                    name: "testnet-terms".to_string(),
+                    label: Some(RelevanceLabel::Testnet),
+                    weight: 3,
+                    keywords: words(&["testnet", "devnet", "faucet", "incentivized", "early access"]),
+                    ..RelevanceRule::default()
+                },
+                RelevanceRule {
+                    name: "giveaway-terms".to_string(),
+                    label: Some(RelevanceLabel::Giveaway),
+                    weight: 3,
+                    keywords: words(&["giveaway", "give away", "raffle", "whitelist", "allowlist", "winners"]),
+                    ..RelevanceRule::default()
+                },
+                RelevanceRule {
+                    name: "quest-platforms".to_string(),
+                    label: Some(RelevanceLabel::Airdrop),
+                    weight: 3,
+                    domains: words(&["galxe.com", "zealy.io", "layer3.xyz", "intract.io", "taskon.xyz"]),
+                    ..RelevanceRule::default()
+                },
+                RelevanceRule {
+                    name: "token-terms".to_string(),
+                    weight: 1,
+                    // The remaining search terms of the level-1 bot.
+                    keywords: words(&["token", "reward", "points"]),
+                    ..RelevanceRule::default()
+                },
+                RelevanceRule {
+                    name: "cashtag".to_string(),
+                    weight: 1,
+                    cashtags: true,
+                    ..RelevanceRule::default()
+                },
+                RelevanceRule {
+                    name: "chain-address".to_string(),
+                    weight: 1,
+                    addresses: true,
+                    ..RelevanceRule::default()
+                },
+                RelevanceRule {
+                    name: "engagement".to_string(),
+                    weight: 1,
+                    min_likes: Some(100),
+                    ..RelevanceRule::default()
+                },
+            ],
+        }
+    }
+}
+
+/// A relevance rule: adds `weight` to the score of a record that meets any of its conditions.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RelevanceRule {
+    /// Name recorded in `matchedRules` when the rule matches.
+    pub name: String,
+    /// The label the rule argues for, `None` for signals shared by every label.
+    pub label: Option<RelevanceLabel>,
+    /// Points added to the score when the rule matches; negative to penalize.
+    pub weight: i32,
+    /// Words or phrases matched case-insensitively at the start of a word, so `reward` also
+    /// matches "rewards".
+    pub keywords: Vec<String>,
+    /// Link domains, subdomains included.
+    pub domains: Vec<String>,
+    /// Matches records with at least one cashtag.
+    pub cashtags: bool,
+    /// Matches records with at least one blockchain address.
+    pub addresses: bool,
+    /// Matches records with at least this many likes.
+    pub min_likes: Option<u64>,
+    /// Matches records with at least this many reposts.
+    pub min_reposts: Option<u64>,
+}
+
+impl RelevanceRule {
+    /// Returns `true` if the rule has at least one condition.
+    pub fn has_condition(&self) -> bool {
+        !self.keywords.is_empty()
+            || !self.domains.is_empty()
+            || self.cashtags
+            || self.addresses
+            || self.min_likes.is_some()
+            || self.min_reposts.is_some()
+    }
+}
+
+/// Settings for `ChainClassifier`, which attaches the chains a record is about.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChainsConfig {
+    /// TOML file mapping keywords, cashtags, domains and handles to chains. `None` uses the
+    /// `data/chains.toml` built into the binary.
+    pub data_path: Option<PathBuf>,
+    /// Confidence below which a chain is not attached to a record.
+    pub min_confidence: f64,
+}
+
+impl Default for ChainsConfig {
+    fn default() -> Self {
+        Self {
+            data_path: None,
+            min_confidence: 0.2,
+        }
+    }
+}
+
+/// Settings for `LinkSafetyChecker`, which flags risky links before records reach users.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LinkSafetyConfig {
+    /// Checks the links of every record and sets their `riskFlags`.
+    pub enabled: bool,
+    /// Text file of known phishing and drainer domains, one per line, `#` for comments.
+    /// Subdomains of a listed domain are blocked too.
+    pub blocklist_path: PathBuf,
+    /// JSON file recording when each domain was first seen, kept across runs.
+    pub seen_domains_path: PathBuf,
+    /// Days a domain counts as freshly seen after its first appearance. 0 disables the check.
+    pub fresh_days: u32,
+    /// Top-level domains mostly used for throwaway phishing sites, without the dot.
+    pub suspicious_tlds: Vec<String>,
+    /// Official domains of platforms and projects, besides the domains in the chain data.
+    /// Lookalikes of these are flagged, and wallet prompts on them are not.
+    pub official_domains: Vec<String>,
+}
+
+impl Default for LinkSafetyConfig {
+    fn default() -> Self {
+        let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+        Self {
+            enabled: true,
+            blocklist_path: PathBuf::from("./data/blocklist.txt"),
+            seen_domains_path: PathBuf::from("./seen_domains.json"),
+            fresh_days: 3,
+            suspicious_tlds: strings(&[
+                "zip", "mov", "top", "click", "icu", "buzz", "cyou", "rest", "sbs", "cfd", "monster", "tk", "ml", "ga", "cf", "gq",
+                "xin", "loan", "work", "support",
+            ]),
+            official_domains: strings(&[
+                "x.com", "twitter.com", "t.co", "discord.gg", "discord.com", "t.me", "telegram.org", "youtube.com", "youtu.be",
+                "medium.com", "mirror.xyz", "paragraph.xyz", "github.com", "gitbook.io", "notion.site", "linktr.ee", "galxe.com",
+                "zealy.io", "layer3.xyz", "intract.io", "taskon.xyz", "guild.xyz", "snapshot.org", "coinbase.com", "binance.com",
+                "okx.com", "bybit.com", "kraken.com", "metamask.io", "rabby.io", "opensea.io", "blur.io", "coingecko.com",
+                "coinmarketcap.com", "dexscreener.com", "defillama.com", "debank.com", "revoke.cash",
+            ]),
+        }
+    }
+}
+
+/// Settings for `ClusterIndex`, which groups tweets announcing the same opportunity.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClusteringConfig {
+    /// Assigns every record a `clusterId` and maintains the clusters collection.
+    pub enabled: bool,
+    /// Largest number of differing SimHash bits (out of 64) for two texts to be near-duplicates.
+    /// Tweets sharing a link may differ by twice as many.
+    pub max_distance: u32,
+    /// Texts with fewer distinct words carry no fingerprint and only cluster through shared
+    /// links and cashtags.
+    pub min_words: usize,
+    /// Days a cluster stays open for new tweets after its last one.
+    pub window_days: u32,
+}
+
+impl Default for ClusteringConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_distance: 14,
+            min_words: 6,
+            window_days: 14,
+        }
+    }
+}
+
+/// Command line flags. Every flag is optional and only overrides the layers below it.
+#[derive(Debug, Parser)]
+#[command(name = "deepness_level_2", about = "Level 2 profile scraper for airdrop tweets")]
+pub struct Cli {
+    /// Path to the TOML configuration file.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Run Chrome without a visible window.
+    #[arg(long, conflicts_with = "headful")]
+    pub headless: bool,
+    /// Run Chrome with a visible window.
+    #[arg(long)]
+    pub headful: bool,
+    /// Directory for the persistent Chrome profile.
+    #[arg(long, value_name = "PATH")]
+    pub user_data_dir: Option<PathBuf>,
+    /// User agent string passed to Chrome.
+    #[arg(long)]
+    pub user_agent: Option<String>,
+    /// Browser window width in pixels.
+    #[arg(long)]
+    pub window_width: Option<u32>,
+    /// Browser window height in pixels.
+    #[arg(long)]
+    pub window_height: Option<u32>,
+    /// Maximum number of tweets collected per profile.
+    #[arg(long)]
+    pub tweet_limit: Option<usize>,
+    /// Scroll cycles without new tweets before scrolling stops.
+    #[arg(long)]
+    pub max_consecutive_failed_cycles: Option<usize>,
+    /// Ignore the profiles' watermarks and scroll up to the tweet limit.
+    #[arg(long)]
+    pub full: bool,
+    /// Open tweets that look like thread starts and collect the author's self-replies.
+    #[arg(long)]
+    pub expand_threads: bool,
+    /// Keep unexpanded t.co links as they are instead of following their redirects.
+    #[arg(long)]
+    pub no_resolve_links: bool,
+    /// Store records scoring below the relevance threshold instead of dropping them.
+    #[arg(long)]
+    pub keep_unrelated: bool,
+    /// Relevance score a record needs to be stored.
+    #[arg(long, value_name = "SCORE")]
+    pub min_relevance_score: Option<i32>,
+    /// Disable the automatic login attempt.
+    #[arg(long)]
+    pub no_login: bool,
+    /// Maximum number of profiles scraped in this run.
+    #[arg(long)]
+    pub max_profiles: Option<usize>,
+    /// Number of concurrent scraper workers.
+    #[arg(long)]
+    pub workers: Option<usize>,
+    /// Backfill every active profile back to this date (YYYY-MM-DD).
+    #[arg(long, value_name = "DATE", conflicts_with = "backfill_days")]
+    pub backfill_until: Option<NaiveDate>,
+    /// Backfill every active profile over the last DAYS days.
+    #[arg(long, value_name = "DAYS")]
+    pub backfill_days: Option<u32>,
+}
+
+impl Config {
+    /// Resolves the configuration from defaults, the TOML file, the environment and
+    /// the process command line, then validates it.
+    ///
+    /// # Returns
+    /// `Result<Config>`: The validated configuration, or an `anyhow::Error` describing
+    /// the first layer that could not be read or the first invalid setting.
+    pub fn load() -> Result<Self> {
+        Self::load_from(Cli::parse())
+    }
+
+    /// Same as `Config::load`, but with already parsed command line flags.
+    pub fn load_from(cli: Cli) -> Result<Self> {
+        let explicit_path = cli
+            .config
+            .clone()
+            .or_else(|| env::var_os(format!("{}CONFIG", ENV_PREFIX)).map(PathBuf::from));
+
+        let mut config = match explicit_path {
+            Some(path) => Self::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => Self::from_file(Path::new(DEFAULT_CONFIG_PATH))?,
+            None => {
+                println!("No {} found, using built-in defaults.", DEFAULT_CONFIG_PATH);
+                Self::default()
+            }
+        };
+
+        config.apply_env()?;
+        config.apply_cli(&cli);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads a configuration from a TOML file. Missing keys fall back to their defaults.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {:?}", path))?;
+        let config = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {:?}", path))?;
+        println!("Loaded configuration from {:?}", path);
+        Ok(config)
+    }
+
+    /// Applies `DEEPNESS_*` environment variable overrides.
+    fn apply_env(&mut self) -> Result<()> {
+        env_override("HEADLESS", &mut self.browser.headless)?;
+        env_override("USER_DATA_DIR", &mut self.browser.user_data_dir)?;
+        env_override("USER_AGENT", &mut self.browser.user_agent)?;
+        env_override("WINDOW_WIDTH", &mut self.browser.window_width)?;
+        env_override("WINDOW_HEIGHT", &mut self.browser.window_height)?;
+
+        env_override("TWEET_LIMIT", &mut self.scraping.tweet_limit)?;
+        env_override("MAX_CONSECUTIVE_FAILED_CYCLES", &mut self.scraping.max_consecutive_failed_cycles)?;
+        env_override("PAGE_LOAD_SETTLE_SECS", &mut self.scraping.page_load_settle_secs)?;
+        env_override("SETTLE_AFTER_SCROLL_SECS", &mut self.scraping.settle_after_scroll_secs)?;
+        env_override("SETTLE_AFTER_RECOVERY_SCROLL_SECS", &mut self.scraping.settle_after_recovery_scroll_secs)?;
+        env_override("INITIAL_ITEM_HEIGHT", &mut self.scraping.initial_item_height)?;
+        env_override("INCREMENTAL", &mut self.scraping.incremental)?;
+        env_override("CAPTURE_GRAPHQL", &mut self.scraping.capture_graphql)?;
+        env_override("EXPAND_THREADS", &mut self.scraping.expand_threads)?;
+        env_override("MAX_THREAD_EXPANSIONS", &mut self.scraping.max_thread_expansions)?;
+        env_override("MAX_THREAD_LENGTH", &mut self.scraping.max_thread_length)?;
+
+        env_override("LOGIN_ENABLED", &mut self.login.enabled)?;
+        env_override("LOGIN_USERNAME", &mut self.login.username)?;
+        env_override("LOGIN_PASSWORD", &mut self.login.password)?;
+        env_override("LOGIN_EMAIL", &mut self.login.email)?;
+
+        // The unprefixed variable is shared with the Express server's `.env`; the prefixed one wins.
+        if let Ok(uri) = env::var("MONGODB_URI") {
+            self.database.uri = uri;
+        }
+        env_override("MONGODB_URI", &mut self.database.uri)?;
+        env_override("DATABASE_NAME", &mut self.database.name)?;
+        env_override("AIRDROPS_COLLECTION", &mut self.database.airdrops_collection)?;
+        env_override("TOKENS_COLLECTION", &mut self.database.tokens_collection)?;
+        env_override("PROFILES_COLLECTION", &mut self.database.profiles_collection)?;
+        env_override("CLUSTERS_COLLECTION", &mut self.database.clusters_collection)?;
+
+        env_override("DEFAULT_INTERVAL_MINUTES", &mut self.watchlist.default_interval_minutes)?;
+        env_override("MAX_PROFILES_PER_RUN", &mut self.watchlist.max_profiles_per_run)?;
+        env_override("FAILURE_BACKOFF_MINUTES", &mut self.watchlist.failure_backoff_minutes)?;
+        env_override("MAX_FAILURE_BACKOFF_MINUTES", &mut self.watchlist.max_failure_backoff_minutes)?;
+
+        env_override("WORKERS", &mut self.pool.workers)?;
+        env_override("MAX_ATTEMPTS", &mut self.pool.max_attempts)?;
+        env_override("RETRY_BACKOFF_SECS", &mut self.pool.retry_backoff_secs)?;
+        env_override("RATE_LIMIT_COOLDOWN_SECS", &mut self.pool.rate_limit_cooldown_secs)?;
+
+        if let Ok(raw) = env::var(format!("{}BACKFILL_UNTIL", ENV_PREFIX)) {
+            let until = raw
+                .trim()
+                .parse()
+                .map_err(|e| anyhow!("Invalid value for {}BACKFILL_UNTIL: {}", ENV_PREFIX, e))?;
+            self.backfill.until = Some(until);
+        }
+        env_override("BACKFILL_MAX_TWEETS", &mut self.backfill.max_tweets)?;
+        env_override("BACKFILL_CHECKPOINT_DIR", &mut self.backfill.checkpoint_dir)?;
+        env_override("BACKFILL_CHECKPOINT_EVERY", &mut self.backfill.checkpoint_every)?;
+
+        env_override("RESOLVE_LINKS", &mut self.links.resolve)?;
+        env_override("LINK_CACHE_PATH", &mut self.links.cache_path)?;
+        env_override("LINK_TIMEOUT_SECS", &mut self.links.timeout_secs)?;
+        env_override("LINK_MAX_REDIRECTS", &mut self.links.max_redirects)?;
+
+        env_override("RELEVANCE_ENABLED", &mut self.relevance.enabled)?;
+        env_override("RELEVANCE_FILTER", &mut self.relevance.filter)?;
+        env_override("RELEVANCE_MIN_SCORE", &mut self.relevance.min_score)?;
+
+        if let Some(path) = env::var_os(format!("{}CHAINS_DATA_PATH", ENV_PREFIX)) {
+            self.chains.data_path = Some(PathBuf::from(path));
+        }
+        env_override("CHAINS_MIN_CONFIDENCE", &mut self.chains.min_confidence)?;
+
+        env_override("LINK_SAFETY_ENABLED", &mut self.link_safety.enabled)?;
+        env_override("LINK_SAFETY_BLOCKLIST_PATH", &mut self.link_safety.blocklist_path)?;
+        env_override("LINK_SAFETY_SEEN_DOMAINS_PATH", &mut self.link_safety.seen_domains_path)?;
+        env_override("LINK_SAFETY_FRESH_DAYS", &mut self.link_safety.fresh_days)?;
+
+        env_override("CLUSTERING_ENABLED", &mut self.clustering.enabled)?;
+        env_override("CLUSTERING_MAX_DISTANCE", &mut self.clustering.max_distance)?;
+        env_override("CLUSTERING_MIN_WORDS", &mut self.clustering.min_words)?;
+        env_override("CLUSTERING_WINDOW_DAYS", &mut self.clustering.window_days)?;
+        Ok(())
+    }
+
+    /// Applies command line flag overrides.
+    fn apply_cli(&mut self, cli: &Cli) {
+        if cli.headless {
+            self.browser.headless = true;
+        }
+        if cli.headful {
+            self.browser.headless = false;
+        }
+        if let Some(dir) = &cli.user_data_dir {
+            self.browser.user_data_dir = dir.clone();
+        }
+        if let Some(user_agent) = &cli.user_agent {
+            self.browser.user_agent = user_agent.clone();
+        }
+        if let Some(width) = cli.window_width {
+            self.browser.window_width = width;
+        }
+        if let Some(height) = cli.window_height {
+            self.browser.window_height = height;
+        }
+        if let Some(limit) = cli.tweet_limit {
+            self.scraping.tweet_limit = limit;
+        }
+        if let Some(cycles) = cli.max_consecutive_failed_cycles {
+            self.scraping.max_consecutive_failed_cycles = cycles;
+        }
+        if cli.full {
+            self.scraping.incremental = false;
+        }
+        if cli.expand_threads {
+            self.scraping.expand_threads = true;
+        }
+        if cli.no_resolve_links {
+            self.links.resolve = false;
+        }
+        if cli.keep_unrelated {
+            self.relevance.filter = false;
+        }
+        if let Some(min_score) = cli.min_relevance_score {
+            self.relevance.min_score = min_score;
+        }
+        if cli.no_login {
+            self.login.enabled = false;
+        }
+        if let Some(max_profiles) = cli.max_profiles {
+            self.watchlist.max_profiles_per_run = max_profiles;
+        }
+        if let Some(workers) = cli.workers {
+            self.pool.workers = workers;
+        }
+        if let Some(until) = cli.backfill_until {
+            self.backfill.until = Some(until);
+        }
+        if let Some(days) = cli.backfill_days {
+            self.backfill.until = Some(Utc::now().date_naive() - chrono::Days::new(u64::from(days)));
+        }
+    }
+
+    /// Checks that the resolved settings are usable.
+    ///
+    /// # Errors
+    /// Returns an error naming the offending setting if a value is out of range.
+    pub fn validate(&self) -> Result<()> {
+        if self.browser.user_data_dir.as_os_str().is_empty() {
+            return Err(anyhow!("browser.user_data_dir must not be empty"));
+        }
+        if self.browser.user_agent.trim().is_empty() {
+            return Err(anyhow!("browser.user_agent must not be empty"));
+        }
+        if self.browser.window_width < 320 || self.browser.window_height < 240 {
+            return Err(anyhow!(
+                "browser window size {}x{} is too small (minimum 320x240)",
+                self.browser.window_width,
+                self.browser.window_height
+            ));
+        }
+        if self.scraping.tweet_limit == 0 {
+            return Err(anyhow!("scraping.tweet_limit must be greater than 0"));
+        }
+        if self.scraping.max_consecutive_failed_cycles == 0 {
+            return Err(anyhow!("scraping.max_consecutive_failed_cycles must be greater than 0"));
+        }
+        if self.scraping.settle_after_recovery_scroll_secs < self.scraping.settle_after_scroll_secs {
+            return Err(anyhow!(
+                "scraping.settle_after_recovery_scroll_secs ({}) must not be shorter than scraping.settle_after_scroll_secs ({})",
+                self.scraping.settle_after_recovery_scroll_secs,
+                self.scraping.settle_after_scroll_secs
+            ));
+        }
+        if !(self.scraping.initial_item_height > 10.0 && self.scraping.initial_item_height.is_finite()) {
+            return Err(anyhow!("scraping.initial_item_height must be a finite value greater than 10"));
+        }
+        if self.scraping.expand_threads && (self.scraping.max_thread_expansions == 0 || self.scraping.max_thread_length == 0) {
+            return Err(anyhow!(
+                "scraping.max_thread_expansions and scraping.max_thread_length must be greater than 0 when scraping.expand_threads is enabled"
+            ));
+        }
+        if !(self.database.uri.starts_with("mongodb://") || self.database.uri.starts_with("mongodb+srv://")) {
+            return Err(anyhow!("database.uri must start with mongodb:// or mongodb+srv://"));
+        }
+        if self.database.name.is_empty()
+            || self.database.airdrops_collection.is_empty()
+            || self.database.tokens_collection.is_empty()
+            || self.database.profiles_collection.is_empty()
+            || self.database.clusters_collection.is_empty()
+        {
+            return Err(anyhow!("database.name and the database collection names must not be empty"));
+        }
+        if self.pool.workers == 0 || self.pool.max_attempts == 0 {
+            return Err(anyhow!("pool.workers and pool.max_attempts must be greater than 0"));
+        }
+        if self.links.resolve && (self.links.timeout_secs == 0 || self.links.cache_path.as_os_str().is_empty()) {
+            return Err(anyhow!("links.timeout_secs must be greater than 0 and links.cache_path must not be empty"));
+        }
+        if self.relevance.enabled {
+            if self.relevance.rules.is_empty() {
+                return Err(anyhow!("relevance.rules must not be empty when relevance.enabled is set"));
+            }
+            if let Some(rule) = self.relevance.rules.iter().find(|rule| rule.name.trim().is_empty() || !rule.has_condition()) {
+                return Err(anyhow!(
+                    "relevance rule {:?} needs a name and at least one of keywords, domains, cashtags, addresses, min_likes or min_reposts",
+                    rule.name
+                ));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.chains.min_confidence) {
+            return Err(anyhow!("chains.min_confidence must be between 0 and 1"));
+        }
+        if let Some(path) = &self.chains.data_path
+            && !path.is_file()
+        {
+            return Err(anyhow!("chains.data_path {:?} is not a file", path));
+        }
+        if self.link_safety.suspicious_tlds.iter().any(|tld| tld.trim_start_matches('.').is_empty()) {
+            return Err(anyhow!("link_safety.suspicious_tlds must not contain empty entries"));
+        }
+        if self.clustering.max_distance > 32 {
+            return Err(anyhow!("clustering.max_distance must be at most 32"));
+        }
+        if self.clustering.min_words == 0 || self.clustering.window_days == 0 {
+            return Err(anyhow!("clustering.min_words and clustering.window_days must be greater than 0"));
+        }
+        if self.backfill.max_tweets == 0 || self.backfill.checkpoint_every == 0 {
+            return Err(anyhow!("backfill.max_tweets and backfill.checkpoint_every must be greater than 0"));
+        }
+        if self.backfill.checkpoint_dir.as_os_str().is_empty() {
+            return Err(anyhow!("backfill.checkpoint_dir must not be empty"));
+        }
+        if let Some(until) = self.backfill.until
+            && until > Utc::now().date_naive()
+        {
+            return Err(anyhow!("backfill.until ({}) must not be in the future", until));
+        }
+        if self.watchlist.max_profiles_per_run == 0 {
+            return Err(anyhow!("watchlist.max_profiles_per_run must be greater than 0"));
+        }
+        if self.login.enabled && (self.login.username.is_empty() || self.login.password.is_empty()) {
+            println!("WARNING: login is enabled but no credentials are configured. Set DEEPNESS_LOGIN_USERNAME and DEEPNESS_LOGIN_PASSWORD.");
+        }
+        Ok(())
+    }
+}
+
+/// Overrides `target` with the parsed value of `DEEPNESS_<key>` if that variable is set.
+///
+/// # Errors
+/// Returns an error if the variable is set but cannot be parsed into the target type.
+fn env_override<T>(key: &str, target: &mut T) -> Result<()>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let name = format!("{}{}", ENV_PREFIX, key);
+    if let Ok(raw) = env::var(&name) {
+        *target = raw
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("Invalid value for {}: {}", name, e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,158 @@
+//! This module provides the `ClusterRepository`, which persists the clusters of near-duplicate
+//! tweets in the `clusters` collection.
+//!
+//! A cluster is one opportunity as announced by several tweets, usually the project's own
+//! tweet and the aggregator accounts rewording it. Each `Airdrop` record points at its cluster
+//! through `clusterId`; the cluster names the canonical record to show and lists the others as
+//! its supporting tweets. The fingerprints, links and cashtags kept on the cluster let later
+//! batches join it.
+
+use crate::errors::ScrapeResult;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    Collection, Database, IndexModel,
+    bson::doc,
+    options::{IndexOptions, UpdateOptions},
+};
+use serde::{Deserialize, Serialize};
+
+use super::tweet_repo::to_bson_datetime;
+
+/// A cluster of tweets announcing the same opportunity, as stored in the `clusters` collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TweetCluster {
+    /// Id of the cluster: the `tweetId` of the tweet that opened it.
+    pub cluster_id: String,
+    /// The record to show for the cluster: the earliest tweet, usually the project's own
+    /// announcement.
+    pub canonical_tweet_id: String,
+    /// Creation time of the canonical tweet.
+    #[serde(default, with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub canonical_created_at: Option<DateTime<Utc>>,
+    /// Ids of every tweet in the cluster, the canonical one included, in the order they joined.
+    pub tweet_ids: Vec<String>,
+    /// Handles of the accounts that posted the tweets, without the leading `@`.
+    #[serde(default)]
+    pub author_handles: Vec<String>,
+    /// SimHash fingerprints of the member texts, as the bit pattern of a signed 64-bit integer.
+    #[serde(default)]
+    pub fingerprints: Vec<i64>,
+    /// Normalized links shared by the members (host and path).
+    #[serde(default)]
+    pub links: Vec<String>,
+    /// Cashtags of the members, upper-cased.
+    #[serde(default)]
+    pub cashtags: Vec<String>,
+    /// When the cluster was opened.
+    #[serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub first_seen_at: DateTime<Utc>,
+    /// When a tweet last joined the cluster.
+    #[serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub last_seen_at: DateTime<Utc>,
+}
+
+/// Repository for `TweetCluster` records in the `clusters` collection.
+#[derive(Clone)]
+pub struct ClusterRepository {
+    collection: Collection<TweetCluster>,
+}
+
+impl ClusterRepository {
+    /// Creates a repository over the given collection of the database.
+    ///
+    /// # Arguments
+    /// * `database` - The database handle returned by `db::connect`.
+    /// * `collection_name` - The name of the clusters collection (`clusters` by default).
+    pub fn new(database: &Database, collection_name: &str) -> Self {
+        Self {
+            collection: database.collection(collection_name),
+        }
+    }
+
+    /// Ensures the unique `clusterId` index and the `tweetIds` and `lastSeenAt` lookup indexes
+    /// exist.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if an index cannot be created.
+    pub async fn ensure_indexes(&self) -> ScrapeResult<()> {
+        let identity = IndexModel::builder()
+            .keys(doc! { "clusterId": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        let by_tweet = IndexModel::builder().keys(doc! { "tweetIds": 1 }).build();
+        let by_activity = IndexModel::builder().keys(doc! { "lastSeenAt": -1 }).build();
+        self.collection.create_indexes([identity, by_tweet, by_activity], None).await?;
+        Ok(())
+    }
+
+    /// Returns the clusters a tweet joined at or after `since`, which are still open for
+    /// new tweets.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if the query fails.
+    pub async fn open_since(&self, since: DateTime<Utc>) -> ScrapeResult<Vec<TweetCluster>> {
+        let filter = doc! { "lastSeenAt": { "$gte": to_bson_datetime(&since) } };
+        let clusters = self.collection.find(filter, None).await?.try_collect().await?;
+        Ok(clusters)
+    }
+
+    /// Returns the clusters containing any of `tweet_ids`, so a re-scraped tweet keeps its
+    /// cluster after the cluster has closed.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if the query fails.
+    pub async fn containing(&self, tweet_ids: &[String]) -> ScrapeResult<Vec<TweetCluster>> {
+        if tweet_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let filter = doc! { "tweetIds": { "$in": tweet_ids } };
+        let clusters = self.collection.find(filter, None).await?.try_collect().await?;
+        Ok(clusters)
+    }
+
+    /// Merges the given clusters into their stored versions, inserting the new ones. Member
+    /// lists are unioned, `firstSeenAt` and `lastSeenAt` only widen, and the canonical record
+    /// is only replaced by an earlier tweet, so a write never drops what another writer added
+    /// since the cluster was loaded.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` on the first failing write.
+    pub async fn save(&self, clusters: &[TweetCluster]) -> ScrapeResult<()> {
+        let options = UpdateOptions::builder().upsert(true).build();
+        for cluster in clusters {
+            let filter = doc! { "clusterId": &cluster.cluster_id };
+            let update = doc! {
+                "$setOnInsert": {
+                    "canonicalTweetId": &cluster.canonical_tweet_id,
+                    "canonicalCreatedAt": cluster.canonical_created_at.as_ref().map(to_bson_datetime),
+                },
+                "$addToSet": {
+                    "tweetIds": { "$each": &cluster.tweet_ids },
+                    "authorHandles": { "$each": &cluster.author_handles },
+                    "fingerprints": { "$each": &cluster.fingerprints },
+                    "links": { "$each": &cluster.links },
+                    "cashtags": { "$each": &cluster.cashtags },
+                },
+                "$min": { "firstSeenAt": to_bson_datetime(&cluster.first_seen_at) },
+                "$max": { "lastSeenAt": to_bson_datetime(&cluster.last_seen_at) },
+            };
+            self.collection.update_one(filter, update, options.clone()).await?;
+
+            // A stored cluster takes the canonical record only if it is earlier than its own.
+            if let Some(created_at) = &cluster.canonical_created_at {
+                let created_at = to_bson_datetime(created_at);
+                let earlier = doc! {
+                    "clusterId": &cluster.cluster_id,
+                    "$or": [{ "canonicalCreatedAt": null }, { "canonicalCreatedAt": { "$gt": &created_at } }],
+                };
+                let canonical = doc! {
+                    "$set": { "canonicalTweetId": &cluster.canonical_tweet_id, "canonicalCreatedAt": created_at },
+                };
+                self.collection.update_one(earlier, canonical, None).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,2 +1,40 @@
-mod tweet_repo;
-mod token_repo;
\ No newline at end of file
+//! This module contains the MongoDB repositories used to read the profile watchlist and
+//! persist scraped data.
+//!
+//! The collections are shared with the Express server (`AirdropModel`) and the Python AI
+//! service, so documents are written with the same camelCase field names they use.
+
+use crate::config::DatabaseConfig;
+use crate::errors::ScrapeResult;
+use mongodb::{Client, Database};
+
+/// Repository for `Airdrop` records, keyed by `tweetId`.
+pub mod tweet_repo;
+/// Repository for token/project entities linked to the tweets that mention them.
+pub mod token_repo;
+/// Repository for the watchlist of profiles to scrape.
+pub mod profile_repo;
+/// Repository for the clusters of near-duplicate tweets.
+pub mod cluster_repo;
+
+/// Re-exports the `TweetRepository` for easy access.
+pub use tweet_repo::TweetRepository;
+/// Re-exports the `TokenRepository` for easy access.
+pub use token_repo::TokenRepository;
+/// Re-exports the `ProfileRepository` for easy access.
+pub use profile_repo::ProfileRepository;
+/// Re-exports the `ClusterRepository` for easy access.
+pub use cluster_repo::ClusterRepository;
+
+/// Connects to MongoDB and returns a handle to the configured database.
+///
+/// # Arguments
+/// * `config` - The database section of the configuration.
+///
+/// # Returns
+/// `ScrapeResult<Database>`: The database handle, or `ScrapeError::Database` if the
+/// connection string is invalid.
+pub async fn connect(config: &DatabaseConfig) -> ScrapeResult<Database> {
+    let client = Client::with_uri_str(&config.uri).await?;
+    Ok(client.database(&config.name))
+}
@@ -0,0 +1,253 @@
+//! This module provides the `ProfileRepository`, which reads the watchlist of X profiles to
+//! scrape from the `profiles` collection and records the outcome of each scrape.
+//!
+//! Profiles are curated directly in the database, so accounts can be added, reprioritized or
+//! paused without recompiling. A minimal document only needs a `handle`:
+//!
+//! ```json
+//! { "handle": "solana", "priority": 10, "intervalMinutes": 30, "chains": ["solana"], "domains": ["solana.com"] }
+//! ```
+
+use crate::config::WatchlistConfig;
+use crate::errors::{Recovery, ScrapeError, ScrapeResult};
+use crate::scraper::{ScrapeTarget, batch::Watermark};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    Collection, Database, IndexModel,
+    bson::doc,
+    options::{FindOptions, IndexOptions},
+};
+use serde::{Deserialize, Serialize};
+
+use super::tweet_repo::to_bson_datetime;
+
+/// A profile on the watchlist, as stored in the `profiles` collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedProfile {
+    /// X handle without the leading `@`, e.g. `solana`.
+    pub handle: String,
+    /// Author id attributed to the scraped tweets. Defaults to the handle.
+    #[serde(default)]
+    pub author_id: Option<String>,
+    /// Higher priorities are scraped first.
+    #[serde(default)]
+    pub priority: i32,
+    /// Paused profiles are never returned as due.
+    #[serde(default = "default_active")]
+    pub active: bool,
+    /// Minutes between two scrapes of this profile. Falls back to the watchlist default.
+    #[serde(default)]
+    pub interval_minutes: Option<u32>,
+    /// When the profile was last scraped successfully.
+    #[serde(default, with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub last_scraped_at: Option<DateTime<Utc>>,
+    /// Id of the newest tweet seen on the profile. Incremental scrapes stop at this tweet.
+    #[serde(default)]
+    pub last_seen_tweet_id: Option<String>,
+    /// Creation time of the newest tweet seen on the profile.
+    #[serde(default, with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub last_seen_tweet_at: Option<DateTime<Utc>>,
+    /// Message of the last failed scrape, cleared by the next successful one.
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// When the last failed scrape happened.
+    #[serde(default, with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub last_error_at: Option<DateTime<Utc>>,
+    /// Number of consecutive failed scrapes, reset by a successful one.
+    #[serde(default)]
+    pub failure_count: u32,
+    /// Ids of the chains the profile belongs to (see `data/chains.toml`), e.g. `["solana"]`.
+    /// Counted as a signal when classifying the profile's tweets by chain.
+    #[serde(default)]
+    pub chains: Vec<String>,
+    /// Domains the profile's project owns, e.g. `["layerzero.network"]`. Links to them in the
+    /// profile's own tweets are trusted by the link safety checks, even when they ask for a
+    /// wallet.
+    #[serde(default)]
+    pub domains: Vec<String>,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+impl WatchedProfile {
+    /// Returns the profile URL to navigate to.
+    pub fn profile_url(&self) -> String {
+        format!("https://x.com/{}", self.handle)
+    }
+
+    /// Returns the author id attributed to the profile's tweets.
+    pub fn author_id(&self) -> String {
+        self.author_id.clone().unwrap_or_else(|| self.handle.clone())
+    }
+
+    /// Returns what a `Scraper` should collect for this profile, including the watermark
+    /// left by the previous successful run.
+    pub fn scrape_target(&self) -> ScrapeTarget {
+        ScrapeTarget {
+            since_tweet_id: self.last_seen_tweet_id.clone(),
+            since_time: self.last_seen_tweet_at,
+            ..ScrapeTarget::new(self.profile_url(), self.author_id())
+        }
+    }
+}
+
+/// Repository for `WatchedProfile` records in the `profiles` collection.
+#[derive(Clone)]
+pub struct ProfileRepository {
+    collection: Collection<WatchedProfile>,
+}
+
+impl ProfileRepository {
+    /// Creates a repository over the given collection of the database.
+    ///
+    /// # Arguments
+    /// * `database` - The database handle returned by `db::connect`.
+    /// * `collection_name` - The name of the profiles collection (`profiles` by default).
+    pub fn new(database: &Database, collection_name: &str) -> Self {
+        Self {
+            collection: database.collection(collection_name),
+        }
+    }
+
+    /// Ensures the unique index on `handle` exists.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if the index cannot be created.
+    pub async fn ensure_indexes(&self) -> ScrapeResult<()> {
+        let index = IndexModel::builder()
+            .keys(doc! { "handle": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.collection.create_index(index, None).await?;
+        Ok(())
+    }
+
+    /// Returns the active profiles whose scrape interval has elapsed and that are not backing
+    /// off from a failure, highest priority first, then fewest consecutive failures first, then
+    /// least recently scraped first.
+    ///
+    /// A profile that failed `failureCount` times in a row waits `failure_backoff_minutes`
+    /// after its last failure, doubled for every failure after the first and capped at
+    /// `max_failure_backoff_minutes`, so a broken profile does not use up a browser session
+    /// and a place in every run.
+    ///
+    /// # Arguments
+    /// * `watchlist` - The watchlist section of the configuration (default interval, run size).
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if the query fails.
+    pub async fn due_profiles(&self, watchlist: &WatchlistConfig) -> ScrapeResult<Vec<WatchedProfile>> {
+        let now = to_bson_datetime(&Utc::now());
+        let backoff_minutes = doc! {
+            "$min": [
+                { "$multiply": [
+                    i64::from(watchlist.failure_backoff_minutes),
+                    { "$pow": [2, { "$subtract": ["$failureCount", 1] }] },
+                ] },
+                i64::from(watchlist.max_failure_backoff_minutes),
+            ]
+        };
+        let filter = doc! {
+            "active": { "$ne": false },
+            "$and": [
+                { "$expr": {
+                    "$or": [
+                        { "$eq": [{ "$ifNull": ["$lastScrapedAt", null] }, null] },
+                        { "$lte": [
+                            "$lastScrapedAt",
+                            { "$subtract": [now.clone(), { "$multiply": [
+                                { "$ifNull": ["$intervalMinutes", i64::from(watchlist.default_interval_minutes)] },
+                                60_000,
+                            ] }] },
+                        ] },
+                    ]
+                } },
+                { "$expr": {
+                    "$or": [
+                        { "$lte": [{ "$ifNull": ["$failureCount", 0] }, 0] },
+                        { "$eq": [{ "$ifNull": ["$lastErrorAt", null] }, null] },
+                        { "$lte": ["$lastErrorAt", { "$subtract": [now, { "$multiply": [backoff_minutes, 60_000] }] }] },
+                    ]
+                } },
+            ],
+        };
+        let profiles = self.collection.find(filter, run_order(watchlist)).await?.try_collect().await?;
+        Ok(profiles)
+    }
+
+    /// Returns the active profiles regardless of their scrape interval, in the same order as
+    /// `due_profiles`. Used by backfill runs.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if the query fails.
+    pub async fn active_profiles(&self, watchlist: &WatchlistConfig) -> ScrapeResult<Vec<WatchedProfile>> {
+        let filter = doc! { "active": { "$ne": false } };
+        let profiles = self.collection.find(filter, run_order(watchlist)).await?.try_collect().await?;
+        Ok(profiles)
+    }
+
+    /// Records a successful scrape of a profile.
+    ///
+    /// # Arguments
+    /// * `handle` - The profile's handle.
+    /// * `watermark` - The newest tweet of the scraped batch, if any. An incremental scrape
+    ///   that found nothing new, or that stopped at the tweet limit before reaching the
+    ///   previous watermark, passes `None` and keeps the previous watermark.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if the write fails.
+    pub async fn mark_scraped(&self, handle: &str, watermark: Option<&Watermark>) -> ScrapeResult<()> {
+        let mut set = doc! {
+            "lastScrapedAt": to_bson_datetime(&Utc::now()),
+            "lastError": null,
+            "failureCount": 0,
+        };
+        if let Some(watermark) = watermark {
+            set.insert("lastSeenTweetId", &watermark.tweet_id);
+            if let Some(created_at) = &watermark.created_at {
+                set.insert("lastSeenTweetAt", to_bson_datetime(created_at));
+            }
+        }
+        self.collection
+            .update_one(doc! { "handle": handle }, doc! { "$set": set }, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Records a failed scrape of a profile and counts it towards the profile's failure
+    /// backoff, unless the bot account rather than the profile failed. Only profiles whose error recommends dropping them (missing, suspended or
+    /// protected accounts) are paused so they stop being returned as due; any other failure,
+    /// such as a selector that broke or a batch that failed to store, leaves the profile
+    /// active and due again once its backoff has passed.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if the write fails.
+    pub async fn record_failure(&self, handle: &str, error: &ScrapeError) -> ScrapeResult<()> {
+        let mut set = doc! {
+            "lastError": error.to_string(),
+            "lastErrorAt": to_bson_datetime(&Utc::now()),
+        };
+        if error.recovery() == Recovery::DropProfile {
+            set.insert("active", false);
+        }
+        let mut update = doc! { "$set": set };
+        if !error.is_account_level() {
+            update.insert("$inc", doc! { "failureCount": 1 });
+        }
+        self.collection.update_one(doc! { "handle": handle }, update, None).await?;
+        Ok(())
+    }
+}
+
+/// Orders the profiles of a run: highest priority first, then fewest consecutive failures
+/// first, then least recently scraped first, limited to the run size.
+fn run_order(watchlist: &WatchlistConfig) -> FindOptions {
+    FindOptions::builder()
+        .sort(doc! { "priority": -1, "failureCount": 1, "lastScrapedAt": 1 })
+        .limit(watchlist.max_profiles_per_run as i64)
+        .build()
+}
@@ -0,0 +1,255 @@
+//! This module provides the `TokenRepository`, which maintains one entity per token/project
+//! in the `tokens` collection and links each entity to the `tweetId`s that mention it.
+//!
+//! Entities are keyed by their normalized ticker and, once known, their contract addresses,
+//! so `$abc` and `ABC` are the same entity while two tokens named `ABC` at different addresses
+//! are not. A mention joins:
+//! - with addresses, the entity of the ticker holding one of them, or else the ticker's entity
+//!   that has no address yet;
+//! - without addresses, the ticker's entity on the mention's chain, or else the ticker's most
+//!   recently seen entity, so a tweet that names no chain does not split the project in two.
+//!
+//! The chain is filled in by the first mention that knows it and kept after. Every mention only
+//! ever adds to an entity: addresses, handles and tweet ids are unioned, `firstSeen` moves
+//! back and `lastSeen` moves forward.
+//!
+//! No name is stored: neither the tweet HTML nor the GraphQL responses name a token, only
+//! its ticker.
+
+use crate::errors::{ScrapeError, ScrapeResult};
+use crate::processing::addresses::AddressKind;
+use crate::processing::chains::ChainClassifier;
+use crate::scraper::user_profile::Airdrop;
+use chrono::{DateTime, Utc};
+use mongodb::{
+    Collection, Database, IndexModel,
+    bson::{Bson, Document, doc, oid::ObjectId},
+    options::{FindOneAndUpdateOptions, FindOneOptions, ReturnDocument},
+};
+use serde::{Deserialize, Serialize};
+
+use super::tweet_repo::to_bson_datetime;
+
+/// A token or project entity as stored in the `tokens` collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenEntity {
+    /// Upper-case ticker without the leading `$`, e.g. `JUP`.
+    pub ticker: String,
+    /// Chain the token lives on (e.g. `solana`), when known.
+    pub chain: Option<String>,
+    /// Known contract or mint addresses.
+    #[serde(default)]
+    pub contract_addresses: Vec<String>,
+    /// Official X handles of the project, without the leading `@`.
+    #[serde(default)]
+    pub official_handles: Vec<String>,
+    /// Ids of the tweets that mention the token.
+    #[serde(default)]
+    pub tweet_ids: Vec<String>,
+    /// Creation time of the earliest tweet seen mentioning the token.
+    #[serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub first_seen: DateTime<Utc>,
+    /// Creation time of the latest tweet seen mentioning the token.
+    #[serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub last_seen: DateTime<Utc>,
+}
+
+/// A single observation of a token in a tweet, to be merged into its `TokenEntity`.
+#[derive(Debug, Clone)]
+pub struct TokenMention {
+    /// Upper-case ticker without the leading `$`.
+    pub ticker: String,
+    /// Chain the token lives on, when known.
+    pub chain: Option<String>,
+    /// Contract or mint addresses found alongside the mention.
+    pub contract_addresses: Vec<String>,
+    /// Official handles of the project, without the leading `@`.
+    pub official_handles: Vec<String>,
+    /// The tweet that mentions the token.
+    pub tweet_id: String,
+    /// When the mention was made (the tweet's creation time, or the scrape time if unknown).
+    pub seen_at: DateTime<Utc>,
+}
+
+impl TokenMention {
+    /// Builds a mention from an `Airdrop` record that has a `tokenName`. The chain is the
+    /// record's most confident `chains` match, and the addresses are the `chainAddresses`
+    /// found on that chain; EVM addresses of no known chain count for every chain but Solana.
+    ///
+    /// The official handles are the accounts whose handle contains the ticker (`jupiterexchange`
+    /// for `JUP`): the author, when it tweets its own cashtag, and the mentioned accounts the
+    /// chain data lists. Tickers under three letters match too many handles to count.
+    ///
+    /// # Arguments
+    /// * `airdrop` - The record, with its addresses detected and its chains classified.
+    /// * `chains` - The chain data, whose handles are trusted.
+    ///
+    /// # Returns
+    /// `Option<TokenMention>`: `None` if the record names no token.
+    pub fn from_airdrop(airdrop: &Airdrop, chains: &ChainClassifier) -> Option<Self> {
+        let ticker = normalize_ticker(airdrop.tokenName.as_deref()?)?;
+        let chain = airdrop.chains.first().map(|chain| chain.chain.clone());
+        let contract_addresses = airdrop
+            .chainAddresses
+            .iter()
+            .filter(|address| matches!(address.kind, AddressKind::Solana | AddressKind::Evm))
+            .filter(|address| match chain.as_deref() {
+                None => true,
+                Some("solana") => address.kind == AddressKind::Solana,
+                Some(chain) => address.chain == chain || (address.kind == AddressKind::Evm && address.chain == "evm"),
+            })
+            .map(|address| address.address.clone())
+            .collect();
+
+        let lower_ticker = ticker.to_lowercase();
+        let owns_ticker = |handle: &str| lower_ticker.len() >= 3 && handle.contains(&lower_ticker);
+        let normalize = |handle: &str| handle.trim().trim_start_matches('@').to_lowercase();
+        let author = airdrop.authorHandle.as_deref().map(normalize).filter(|author| owns_ticker(author));
+        let mut official_handles: Vec<String> = author.into_iter().collect();
+        for handle in airdrop.mentionedUsers.iter().map(|handle| normalize(handle)) {
+            if owns_ticker(&handle) && chains.is_listed_handle(&handle) && !official_handles.contains(&handle) {
+                official_handles.push(handle);
+            }
+        }
+        Some(Self {
+            ticker,
+            chain,
+            contract_addresses,
+            official_handles,
+            tweet_id: airdrop.tweetId.clone(),
+            seen_at: airdrop.createdAt.unwrap_or(airdrop.savedAt),
+        })
+    }
+}
+
+/// Normalizes a ticker: strips a leading `$`, trims and upper-cases it.
+///
+/// # Returns
+/// `Option<String>`: `None` if nothing is left after normalization.
+pub fn normalize_ticker(raw: &str) -> Option<String> {
+    let ticker = raw.trim().trim_start_matches('$').trim().to_uppercase();
+    (!ticker.is_empty()).then_some(ticker)
+}
+
+/// Repository for `TokenEntity` records in the `tokens` collection.
+#[derive(Clone)]
+pub struct TokenRepository {
+    collection: Collection<TokenEntity>,
+}
+
+impl TokenRepository {
+    /// Creates a repository over the given collection of the database.
+    ///
+    /// # Arguments
+    /// * `database` - The database handle returned by `db::connect`.
+    /// * `collection_name` - The name of the tokens collection (`tokens` by default).
+    pub fn new(database: &Database, collection_name: &str) -> Self {
+        Self {
+            collection: database.collection(collection_name),
+        }
+    }
+
+    /// Ensures the `ticker`, `contractAddresses` and `tweetIds` lookup indexes exist.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if an index cannot be created.
+    pub async fn ensure_indexes(&self) -> ScrapeResult<()> {
+        let by_ticker = IndexModel::builder().keys(doc! { "ticker": 1, "lastSeen": -1 }).build();
+        let by_address = IndexModel::builder().keys(doc! { "contractAddresses": 1 }).build();
+        let by_tweet = IndexModel::builder().keys(doc! { "tweetIds": 1 }).build();
+        self.collection.create_indexes([by_ticker, by_address, by_tweet], None).await?;
+        Ok(())
+    }
+
+    /// Merges a mention into its entity, creating the entity on first sight.
+    ///
+    /// # Arguments
+    /// * `mention` - The observed mention.
+    ///
+    /// # Returns
+    /// `ScrapeResult<TokenEntity>`: The entity as stored after the merge.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if the write fails.
+    pub async fn record_mention(&self, mention: &TokenMention) -> ScrapeResult<TokenEntity> {
+        let id = match self.entity_id(mention).await? {
+            Some(id) => id,
+            None => Bson::ObjectId(ObjectId::new()),
+        };
+        // An entity first seen without a chain takes the first one a mention knows.
+        if let Some(chain) = &mention.chain {
+            self.collection
+                .update_one(doc! { "_id": &id, "chain": Bson::Null }, doc! { "$set": { "chain": chain } }, None)
+                .await?;
+        }
+
+        let seen_at = to_bson_datetime(&mention.seen_at);
+        let update = doc! {
+            "$setOnInsert": { "ticker": &mention.ticker, "chain": &mention.chain },
+            "$min": { "firstSeen": seen_at.clone() },
+            "$max": { "lastSeen": seen_at },
+            "$addToSet": {
+                "contractAddresses": { "$each": &mention.contract_addresses },
+                "officialHandles": { "$each": &mention.official_handles },
+                "tweetIds": &mention.tweet_id,
+            },
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .build();
+        let entity = self
+            .collection
+            .find_one_and_update(doc! { "_id": id }, update, options)
+            .await?;
+        // With `upsert` and `ReturnDocument::After` the driver always returns a document.
+        entity.ok_or_else(|| ScrapeError::Parse(format!("token {} was not returned after upsert", mention.ticker)))
+    }
+
+    /// Returns the `_id` of the entity `mention` belongs to, as described in the module docs.
+    ///
+    /// # Returns
+    /// `ScrapeResult<Option<Bson>>`: `None` if the mention is the first of its token.
+    async fn entity_id(&self, mention: &TokenMention) -> ScrapeResult<Option<Bson>> {
+        let ticker = &mention.ticker;
+        let mut filters = Vec::new();
+        if mention.contract_addresses.is_empty() {
+            if let Some(chain) = &mention.chain {
+                filters.push(doc! { "ticker": ticker, "chain": chain });
+            }
+            filters.push(doc! { "ticker": ticker });
+        } else {
+            filters.push(doc! { "ticker": ticker, "contractAddresses": { "$in": &mention.contract_addresses } });
+            filters.push(doc! { "ticker": ticker, "contractAddresses.0": { "$exists": false } });
+        }
+
+        let entities = self.collection.clone_with_type::<Document>();
+        let options = FindOneOptions::builder()
+            .projection(doc! { "_id": 1 })
+            .sort(doc! { "lastSeen": -1 })
+            .build();
+        for filter in filters {
+            if let Some(entity) = entities.find_one(filter, options.clone()).await? {
+                return Ok(entity.get("_id").cloned());
+            }
+        }
+        Ok(None)
+    }
+
+    /// Records the token mentions of a batch of `Airdrop` records.
+    ///
+    /// # Arguments
+    /// * `airdrops` - The records, with their chains classified.
+    /// * `chains` - The chain data, whose listed handles can be official handles.
+    ///
+    /// # Returns
+    /// `ScrapeResult<Vec<TokenEntity>>`: The entities touched by the batch, one per mention.
+    pub async fn record_airdrops(&self, airdrops: &[Airdrop], chains: &ChainClassifier) -> ScrapeResult<Vec<TokenEntity>> {
+        let mut entities = Vec::new();
+        for mention in airdrops.iter().filter_map(|airdrop| TokenMention::from_airdrop(airdrop, chains)) {
+            entities.push(self.record_mention(&mention).await?);
+        }
+        Ok(entities)
+    }
+}
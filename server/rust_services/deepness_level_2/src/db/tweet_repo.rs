@@ -0,0 +1,241 @@
+//! This module provides the `TweetRepository`, which persists processed `Airdrop` records
+//! into the `airdrops` collection shared with the Express server's `AirdropModel`.
+//!
+//! Records are upserted by their unique `tweetId`. Because the level-1 TypeScript bot and
+//! this level-2 scraper write to the same collection, every write is merged field by field
+//! instead of replacing the stored document:
+//! - `deepness` only ever increases.
+//! - Scalar fields are overwritten only by a write at least as deep as the stored record,
+//!   or when the stored value is missing or empty.
+//! - Array fields (`keywords`, `mentionedUsers`, `cashtags`, `hashtags`, `links`,
+//!   `chainAddresses`) are unioned, never shrunk, except that `links` is replaced once all of
+//!   its shortlinks are expanded.
+//! - `resolvedLinks` and the `riskFlags` derived from them are replaced together, so a flag
+//!   the links no longer raise is dropped.
+//! - `savedAt` keeps the time the tweet was first stored.
+//! - `engagement` is a snapshot that only grows stale, so the latest observed counts replace
+//!   the stored ones regardless of deepness.
+
+use crate::errors::ScrapeResult;
+use crate::processing::links::is_shortlink;
+use crate::scraper::user_profile::Airdrop;
+use chrono::{DateTime, Utc};
+use mongodb::{
+    Collection, Database, IndexModel,
+    bson::{self, Bson, Document, doc},
+    options::{IndexOptions, UpdateModifications, UpdateOptions},
+};
+
+/// Counts of what an upsert of a batch did to the collection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpsertSummary {
+    /// Records that did not exist before and were inserted.
+    pub inserted: usize,
+    /// Existing records that were changed by the merge.
+    pub updated: usize,
+    /// Existing records the merge left untouched (e.g. a shallower write with nothing new).
+    pub unchanged: usize,
+}
+
+/// Repository for `Airdrop` records in the `airdrops` collection.
+#[derive(Clone)]
+pub struct TweetRepository {
+    collection: Collection<Document>,
+}
+
+impl TweetRepository {
+    /// Creates a repository over the given collection of the database.
+    ///
+    /// # Arguments
+    /// * `database` - The database handle returned by `db::connect`.
+    /// * `collection_name` - The name of the airdrops collection (`airdrops` by default).
+    pub fn new(database: &Database, collection_name: &str) -> Self {
+        Self {
+            collection: database.collection(collection_name),
+        }
+    }
+
+    /// Ensures the unique index on `tweetId` exists. Mongoose creates the same index, so this
+    /// is a no-op when the Express server has already run against the database.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Database` if the index cannot be created, for example because
+    /// the collection already contains duplicate `tweetId`s.
+    pub async fn ensure_indexes(&self) -> ScrapeResult<()> {
+        let index = IndexModel::builder()
+            .keys(doc! { "tweetId": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.collection.create_index(index, None).await?;
+        // Lets the frontend list open opportunities by urgency.
+        let deadline_index = IndexModel::builder().keys(doc! { "nextDeadline": 1 }).build();
+        self.collection.create_index(deadline_index, None).await?;
+        // Lets the frontend filter by chain.
+        let chain_index = IndexModel::builder().keys(doc! { "chains.chain": 1 }).build();
+        self.collection.create_index(chain_index, None).await?;
+        // Lets the frontend list the supporting tweets of a cluster.
+        let cluster_index = IndexModel::builder().keys(doc! { "clusterId": 1 }).build();
+        self.collection.create_index(cluster_index, None).await?;
+        Ok(())
+    }
+
+    /// Upserts a batch of `Airdrop` records, merging each with any stored record of the same
+    /// `tweetId` according to the deepness rules described in the module documentation.
+    ///
+    /// Every record is a separate `update_one`: the merge is an update pipeline, which the
+    /// driver cannot batch. A failing record does not stop the others from being written.
+    ///
+    /// # Arguments
+    /// * `airdrops` - The records to persist.
+    ///
+    /// # Returns
+    /// `ScrapeResult<UpsertSummary>`: How many records were inserted, updated or left unchanged.
+    ///
+    /// # Errors
+    /// Returns the first `ScrapeError::Database` once every record has been tried. The other
+    /// records are persisted; upserts are idempotent, so the whole batch can be retried.
+    pub async fn upsert_each(&self, airdrops: &[Airdrop]) -> ScrapeResult<UpsertSummary> {
+        let mut summary = UpsertSummary::default();
+        let mut first_error = None;
+        let options = UpdateOptions::builder().upsert(true).build();
+
+        for airdrop in airdrops {
+            let result = self
+                .collection
+                .update_one(
+                    doc! { "tweetId": &airdrop.tweetId },
+                    UpdateModifications::Pipeline(merge_pipeline(airdrop)),
+                    options.clone(),
+                )
+                .await;
+
+            match result {
+                Ok(result) if result.upserted_id.is_some() => summary.inserted += 1,
+                Ok(result) if result.modified_count > 0 => summary.updated += 1,
+                Ok(_) => summary.unchanged += 1,
+                Err(e) => {
+                    eprintln!("Failed to store tweet {}: {}", airdrop.tweetId, e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e.into()),
+            None => Ok(summary),
+        }
+    }
+}
+
+/// Builds the update pipeline that merges `airdrop` into the stored document.
+///
+/// A single `$set` stage is used so that every expression sees the stored document as it was
+/// before the write, in particular the stored `deepness`.
+fn merge_pipeline(airdrop: &Airdrop) -> Vec<Document> {
+    let deepness = i64::from(airdrop.deepness);
+    let incoming_wins = Bson::Document(doc! {
+        "$gte": [deepness, { "$ifNull": ["$deepness", 0] }]
+    });
+
+    let mut fields = Document::new();
+    fields.insert("tweetId", &airdrop.tweetId);
+    fields.insert("text", merge_scalar("text", airdrop.text.clone().map(Bson::String), &incoming_wins));
+    fields.insert("authorId", merge_scalar("authorId", airdrop.authorId.clone().map(Bson::String), &incoming_wins));
+    fields.insert("createdAt", merge_scalar("createdAt", airdrop.createdAt.as_ref().map(to_bson_datetime), &incoming_wins));
+    fields.insert("tokenName", merge_scalar("tokenName", airdrop.tokenName.clone().map(Bson::String), &incoming_wins));
+    fields.insert("keywords", merge_array("keywords", &airdrop.keywords));
+    fields.insert("mentionedUsers", merge_array("mentionedUsers", &airdrop.mentionedUsers));
+    fields.insert("cashtags", merge_array("cashtags", &airdrop.cashtags));
+    fields.insert("hashtags", merge_array("hashtags", &airdrop.hashtags));
+    // Once every link is expanded, the expanded URLs replace the shortlinks stored before.
+    let links = if airdrop.links.is_empty() || airdrop.links.iter().any(|link| is_shortlink(link)) {
+        merge_array("links", &airdrop.links)
+    } else {
+        merge_scalar("links", bson::to_bson(&airdrop.links).ok(), &incoming_wins)
+    };
+    fields.insert("links", links);
+    fields.insert("chainAddresses", merge_array("chainAddresses", &airdrop.chainAddresses));
+    let engagement = airdrop.engagement.as_ref().and_then(|engagement| bson::to_bson(engagement).ok());
+    fields.insert("engagement", merge_scalar("engagement", engagement, &Bson::Boolean(true)));
+    let tweet_type = airdrop.tweetType.and_then(|tweet_type| bson::to_bson(&tweet_type).ok());
+    fields.insert("tweetType", merge_scalar("tweetType", tweet_type, &incoming_wins));
+    fields.insert("authorHandle", merge_scalar("authorHandle", airdrop.authorHandle.clone().map(Bson::String), &incoming_wins));
+    fields.insert("repostedBy", merge_scalar("repostedBy", airdrop.repostedBy.clone().map(Bson::String), &incoming_wins));
+    fields.insert(
+        "inReplyToTweetId",
+        merge_scalar("inReplyToTweetId", airdrop.inReplyToTweetId.clone().map(Bson::String), &incoming_wins),
+    );
+    fields.insert(
+        "inReplyToHandle",
+        merge_scalar("inReplyToHandle", airdrop.inReplyToHandle.clone().map(Bson::String), &incoming_wins),
+    );
+    fields.insert("quotedTweetId", merge_scalar("quotedTweetId", airdrop.quotedTweetId.clone().map(Bson::String), &incoming_wins));
+    let quoted_tweet = airdrop.quotedTweet.as_ref().and_then(|quoted_tweet| bson::to_bson(quoted_tweet).ok());
+    fields.insert("quotedTweet", merge_scalar("quotedTweet", quoted_tweet, &incoming_wins));
+    fields.insert("threadRootId", merge_scalar("threadRootId", airdrop.threadRootId.clone().map(Bson::String), &incoming_wins));
+    let thread_position = airdrop.threadPosition.map(|position| Bson::Int64(i64::from(position)));
+    fields.insert("threadPosition", merge_scalar("threadPosition", thread_position, &incoming_wins));
+    let media = airdrop.media.as_ref().and_then(|media| bson::to_bson(media).ok());
+    fields.insert("media", merge_scalar("media", media, &incoming_wins));
+    let resolved_links = (!airdrop.resolvedLinks.is_empty())
+        .then(|| bson::to_bson(&airdrop.resolvedLinks).ok())
+        .flatten();
+    fields.insert("resolvedLinks", merge_scalar("resolvedLinks", resolved_links, &incoming_wins));
+    // The flags are derived from the links, so they are replaced along with them, which also
+    // drops a flag the links no longer raise.
+    let risk_flags = (!airdrop.resolvedLinks.is_empty()).then(|| bson::to_bson(&airdrop.riskFlags).ok()).flatten();
+    fields.insert("riskFlags", merge_scalar("riskFlags", risk_flags, &incoming_wins));
+    // The checklist is ordered, so it is replaced as a whole rather than unioned.
+    let tasks = (!airdrop.tasks.is_empty()).then(|| bson::to_bson(&airdrop.tasks).ok()).flatten();
+    fields.insert("tasks", merge_scalar("tasks", tasks, &incoming_wins));
+    let deadlines = (!airdrop.deadlines.is_empty()).then(|| bson::to_bson(&airdrop.deadlines).ok()).flatten();
+    fields.insert("deadlines", merge_scalar("deadlines", deadlines, &incoming_wins));
+    fields.insert("nextDeadline", merge_scalar("nextDeadline", airdrop.nextDeadline.as_ref().map(to_bson_datetime), &incoming_wins));
+    let rewards = (!airdrop.rewards.is_empty()).then(|| bson::to_bson(&airdrop.rewards).ok()).flatten();
+    fields.insert("rewards", merge_scalar("rewards", rewards, &incoming_wins));
+    let chains = (!airdrop.chains.is_empty()).then(|| bson::to_bson(&airdrop.chains).ok()).flatten();
+    fields.insert("chains", merge_scalar("chains", chains, &incoming_wins));
+    let relevance = airdrop.relevance.as_ref().and_then(|relevance| bson::to_bson(relevance).ok());
+    fields.insert("relevance", merge_scalar("relevance", relevance, &incoming_wins));
+    // A tweet stays in the cluster it first joined.
+    fields.insert("clusterId", doc! { "$ifNull": ["$clusterId", { "$literal": airdrop.clusterId.clone() }] });
+    fields.insert("deepness", doc! { "$max": [{ "$ifNull": ["$deepness", 0] }, deepness] });
+    fields.insert("savedAt", doc! { "$ifNull": ["$savedAt", to_bson_datetime(&airdrop.savedAt)] });
+
+    vec![doc! { "$set": fields }]
+}
+
+/// Builds the expression for a scalar field.
+///
+/// The incoming value replaces the stored one if `incoming_wins` holds or the stored value is
+/// missing, null or an empty string. A `None` incoming value never erases the stored one.
+fn merge_scalar(field: &str, value: Option<Bson>, incoming_wins: &Bson) -> Bson {
+    let current = format!("${}", field);
+    match value {
+        None => Bson::String(current),
+        Some(value) => Bson::Document(doc! {
+            "$cond": [
+                { "$or": [incoming_wins.clone(), { "$in": [{ "$ifNull": [&current, Bson::Null] }, [Bson::Null, ""]] }] },
+                // `$literal` keeps values such as "$SOL" from being read as field paths.
+                { "$literal": value },
+                &current,
+            ]
+        }),
+    }
+}
+
+/// Builds the expression for an array field: the union of the stored and incoming values.
+pub(crate) fn merge_array<T>(field: &str, values: &[T]) -> Bson
+where
+    T: serde::Serialize,
+{
+    let incoming = bson::to_bson(values).unwrap_or_else(|_| Bson::Array(Vec::new()));
+    Bson::Document(doc! {
+        "$setUnion": [{ "$ifNull": [format!("${}", field), []] }, { "$literal": incoming }]
+    })
+}
+
+/// Converts a `chrono` timestamp into a BSON date, matching the `Date` fields of the Mongoose schema.
+pub(crate) fn to_bson_datetime(value: &DateTime<Utc>) -> Bson {
+    Bson::DateTime(bson::DateTime::from_chrono(*value))
+}
@@ -0,0 +1,154 @@
+//! This module defines the typed error taxonomy for scraping failures.
+//!
+//! Every failure surfaced by the scraper, the login flow, the HTML processor and the
+//! repositories is a `ScrapeError`. Each variant carries an `ErrorClass` (retryable or fatal)
+//! and a `Recovery` hint so an orchestrator can decide whether to retry the same profile
+//! later, rotate to another bot account, skip the profile until its next run, or drop the
+//! profile entirely.
+
+use std::time::Duration;
+use thiserror::Error;
+
+/// A `Result` alias whose error type is `ScrapeError`.
+pub type ScrapeResult<T> = std::result::Result<T, ScrapeError>;
+
+/// Whether retrying the failed operation can ever succeed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The failure is transient or tied to the bot account; the profile can be retried.
+    Retryable,
+    /// The failure is tied to the profile or the input; retrying will fail the same way.
+    Fatal,
+}
+
+/// The action an orchestrator should take after a failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Retry the same profile with the same account, after a backoff.
+    Retry,
+    /// The bot account is blocked or logged out and needs another account or a human. Every
+    /// worker shares the one bot account, so `ScraperPool` stops handing out profiles.
+    RotateAccount,
+    /// Record the failure and skip the profile until its next run. Retrying now would fail
+    /// the same way, but the cause (e.g. a changed page layout) lies with the scraper, not
+    /// the profile.
+    SkipProfile,
+    /// Stop scraping this profile: it no longer exists or cannot be seen.
+    DropProfile,
+}
+
+/// Errors produced while scraping a profile or processing the scraped HTML.
+#[derive(Debug, Error)]
+pub enum ScrapeError {
+    /// The page requires a logged-in session but login is disabled or the session expired.
+    #[error("login required to view {url}")]
+    LoginRequired { url: String },
+
+    /// The login flow ran but could not be confirmed.
+    #[error("login failed: {reason}")]
+    LoginFailed { reason: String },
+
+    /// The login flow hit a CAPTCHA, 2FA or other challenge that needs a human.
+    #[error("login challenge requires manual action: {challenge}")]
+    LoginChallenge { challenge: String },
+
+    /// The bot account used for scraping is suspended or locked.
+    #[error("bot account is suspended or locked (redirected to {url})")]
+    AccountSuspended { url: String },
+
+    /// The target profile does not exist.
+    #[error("profile {url} does not exist")]
+    ProfileNotFound { url: String },
+
+    /// The target profile has been suspended by the platform.
+    #[error("profile {url} is suspended")]
+    ProfileSuspended { url: String },
+
+    /// The target profile's posts are protected and not visible to the bot account.
+    #[error("profile {url} is protected")]
+    ProfileProtected { url: String },
+
+    /// The platform is throttling the bot account.
+    #[error("rate limited{}", .retry_after.map(|d| format!(" (retry after {}s)", d.as_secs())).unwrap_or_default())]
+    RateLimited { retry_after: Option<Duration> },
+
+    /// A CSS selector expected on the page matched no element.
+    #[error("selector `{selector}` matched nothing on {url}")]
+    SelectorNotFound { selector: String, url: String },
+
+    /// A CSS selector could not be parsed. This is a bug in the scraper, not in the page.
+    #[error("invalid selector `{selector}`: {reason}")]
+    InvalidSelector { selector: String, reason: String },
+
+    /// The scraped HTML or batch could not be turned into structured data.
+    #[error("failed to process scraped content: {0}")]
+    Parse(String),
+
+    /// The browser or the DevTools connection failed (navigation, evaluation, element lookup).
+    #[error("browser error: {0}")]
+    Browser(String),
+
+    /// A local I/O operation failed (profile directory, cookie file, screenshots).
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// A MongoDB operation failed while persisting or reading scraped data.
+    #[error("database error: {0}")]
+    Database(#[from] mongodb::error::Error),
+}
+
+impl ScrapeError {
+    /// Returns whether the failed profile can be retried at all.
+    pub fn class(&self) -> ErrorClass {
+        match self.recovery() {
+            Recovery::Retry | Recovery::RotateAccount => ErrorClass::Retryable,
+            Recovery::SkipProfile | Recovery::DropProfile => ErrorClass::Fatal,
+        }
+    }
+
+    /// Returns `true` if the failure lies with the bot account (blocked, logged out or
+    /// throttled) rather than with the profile being scraped.
+    pub fn is_account_level(&self) -> bool {
+        self.recovery() == Recovery::RotateAccount || matches!(self, ScrapeError::RateLimited { .. })
+    }
+
+    /// Returns a copy of an account-level error, e.g. to report it for the profiles a stopped
+    /// pool leaves unscraped. Any other error is copied as a `Browser` error with its message.
+    pub fn copy_account_error(&self) -> ScrapeError {
+        match self {
+            ScrapeError::LoginRequired { url } => ScrapeError::LoginRequired { url: url.clone() },
+            ScrapeError::LoginFailed { reason } => ScrapeError::LoginFailed { reason: reason.clone() },
+            ScrapeError::LoginChallenge { challenge } => ScrapeError::LoginChallenge { challenge: challenge.clone() },
+            ScrapeError::AccountSuspended { url } => ScrapeError::AccountSuspended { url: url.clone() },
+            ScrapeError::RateLimited { retry_after } => ScrapeError::RateLimited { retry_after: *retry_after },
+            other => ScrapeError::Browser(other.to_string()),
+        }
+    }
+
+    /// Returns the recommended action for an orchestrator.
+    pub fn recovery(&self) -> Recovery {
+        match self {
+            ScrapeError::RateLimited { .. }
+            | ScrapeError::SelectorNotFound { .. }
+            | ScrapeError::Browser(_)
+            | ScrapeError::Io(_)
+            | ScrapeError::Database(_) => Recovery::Retry,
+            ScrapeError::LoginRequired { .. }
+            | ScrapeError::LoginFailed { .. }
+            | ScrapeError::LoginChallenge { .. }
+            | ScrapeError::AccountSuspended { .. } => Recovery::RotateAccount,
+            ScrapeError::InvalidSelector { .. } | ScrapeError::Parse(_) => Recovery::SkipProfile,
+            ScrapeError::ProfileNotFound { .. }
+            | ScrapeError::ProfileSuspended { .. }
+            | ScrapeError::ProfileProtected { .. } => Recovery::DropProfile,
+        }
+    }
+}
+
+// `headless_chrome` reports every failure as an `anyhow::Error`, so anything coming out of
+// a `Tab` or `Element` call is treated as a (retryable) browser error.
+impl From<anyhow::Error> for ScrapeError {
+    fn from(e: anyhow::Error) -> Self {
+        ScrapeError::Browser(e.to_string())
+    }
+}
@@ -2,13 +2,12 @@
 //! It orchestrates the web scraping process, from initializing the scraper
 //! to processing the collected data.
 //!
-//! In the future, this module will be enhanced to pull Twitter profiles
-//! from a database and distribute them among several concurrent scrapers
-//! for efficient and scalable data collection.
+//! Profiles are pulled from the watchlist in the database and distributed among
+//! several concurrent scrapers for efficient and scalable data collection.
 
 // Declare the modules within the current crate.
 mod config;      // Configuration settings for the application.
-mod db;          // Database interaction utilities (future: to pull profiles, store data).
+mod db;          // Database repositories for the profile watchlist and scraped data.
 mod scraper;     // Web scraping components.
 mod utils;       // General utility functions.
 mod errors;      // Custom error types for the application.
@@ -16,77 +15,315 @@ mod processing;  // Data processing and transformation components.
 
 // Import necessary items from declared modules.
 use anyhow::Result;
-use scraper::user_profile::UserProfileScraper;
-use processing::html_processor::process_html_set_to_airdrops;
+use config::Config;
+use db::{ClusterRepository, ProfileRepository, TokenRepository, TweetRepository, profile_repo::WatchedProfile};
+use errors::ScrapeResult;
+use scraper::{batch::ScrapedBatch, checkpoint::BackfillCheckpoint, pool::{PoolOutcome, ScraperPool}};
+use processing::{
+    HtmlSetProcessor, addresses, chains::ChainClassifier, clustering::ClusterIndex, deadlines,
+    graphql_processor::GraphqlProcessor, link_safety::LinkSafetyChecker, links::LinkResolver,
+    relevance::RelevanceScorer, rewards, tasks, thread::thread_text,
+};
+use std::sync::Arc;
 
 /// The main function where the application execution begins.
 ///
-/// This function currently demonstrates a single-profile scraping flow:
-/// 1. Initializes a `UserProfileScraper`.
-/// 2. Defines a hardcoded target profile URL and author ID.
-/// 3. Initiates the scraping process to collect raw HTML tweets.
-/// 4. Processes the collected HTML into structured `Airdrop` data.
-/// 5. Prints a summary of the processed `Airdrop` records.
-///
-/// **Future Enhancements:**
-/// This `main` function is designed to evolve. In upcoming iterations,
-/// a dedicated module (likely within `db` or a new `scheduler` module)
-/// will be introduced to:
-/// - Pull a list of Twitter profiles (e.g., specific user handles or search queries)
-///   from a database.
-/// - Manage a pool of `UserProfileScraper` instances.
-/// - Distribute the scraping tasks to these concurrent scrapers, allowing
-///   for parallel processing and significantly faster data acquisition
-///   from multiple profiles simultaneously.
-/// - Once scraped, the raw HTML or processed `Airdrop` data will then be
-///   persisted back into the database for further analysis (e.g., by AI models).
+/// This function runs one pass over the profile watchlist:
+/// 1. Loads the layered `Config` (defaults, TOML file, environment, CLI flags) and
+///    connects to MongoDB.
+/// 2. Pulls the profiles that are due from the `profiles` collection through the
+///    `ProfileRepository`, highest priority first. A backfill run (`backfill.until`) takes
+///    every active profile instead.
+/// 3. Distributes the profiles across a `ScraperPool` of concurrent `UserProfileScraper`
+///    workers, each scraping profiles into `ScrapedBatch`es of raw HTML tweets.
+/// 4. As each batch arrives, processes the captured GraphQL responses, with the collected
+///    HTML as the fallback, into structured `Airdrop` data and persists it (see `store_batch`).
+/// 5. Records the outcome on the profile: the scrape time and the watermark (the newest tweet,
+///    where the next incremental scrape stops, unless the tweet limit cut the scrape short of
+///    the previous one) on success,
+///    the error on failure (pausing profiles that should be dropped).
 ///
 /// # Returns
 /// `Result<()>`: `Ok(())` if the entire process completes successfully,
-/// otherwise an `anyhow::Error` if any step (initialization, scraping, or processing) fails.
-fn main() -> Result<()> {
-    // 1. Initialize the web scraper.
-    let scraper = UserProfileScraper::new()?;
-
-    // 2. Define the target profile for the current single-threaded demonstration.
-    // In the future, these values will be dynamically fetched from a database.
-    let profile_url = "https://x.com/solana";
-    let author_id = "solana_official";
-
-    println!("Starting the scraping process...");
-    println!("Target profile: {}\nAuthor ID: {}", profile_url, author_id);
-
-    // 3. Execute the scraping operation.
-    match scraper.scrape_user_posts(profile_url, author_id) {
-        Ok(html_set) => {
-            println!("\nScraping complete. Found {} HTML posts.", html_set.len());
-            println!("Processing the scraped content into Airdrop data...");
-
-            // 4. Process the collected raw HTML into structured `Airdrop` data.
-            match process_html_set_to_airdrops(&html_set) {
-                Ok(airdrops) => {
-                    println!("\nProcessed {} Airdrop records:", airdrops.len());
-                    // 5. Print out the details of each processed Airdrop.
-                    for (i, airdrop) in airdrops.iter().enumerate() {
-                        println!("\n{}. Tweet ID: {}", i + 1, airdrop.tweetId);
-                        println!("    Text       : {}", airdrop.text.as_deref().unwrap_or("No text"));
-                        println!("    Author ID  : {}", airdrop.authorId.as_deref().unwrap_or("Unknown"));
-                        println!("    Created At : {:?}", airdrop.createdAt);
-                        println!("    Mentions   : {:?}", airdrop.mentionedUsers);
-                        println!("    Links      : {:?}", airdrop.links);
-                        println!("----------------------------------------------------");
+/// otherwise an `anyhow::Error` if any step (configuration, database connection or initialization) fails.
+/// Failures of individual profiles are recorded on the profile and do not stop the run.
+#[tokio::main]
+async fn main() -> Result<()> {
+    // 1. Resolve the configuration and connect to the database.
+    let config = Config::load()?;
+    let database = db::connect(&config.database).await?;
+    let tweet_repo = TweetRepository::new(&database, &config.database.airdrops_collection);
+    tweet_repo.ensure_indexes().await?;
+    let token_repo = TokenRepository::new(&database, &config.database.tokens_collection);
+    token_repo.ensure_indexes().await?;
+    let profile_repo = ProfileRepository::new(&database, &config.database.profiles_collection);
+    profile_repo.ensure_indexes().await?;
+    let cluster_repo = ClusterRepository::new(&database, &config.database.clusters_collection);
+    cluster_repo.ensure_indexes().await?;
+    let chain_classifier = ChainClassifier::load(&config.chains)?;
+    let mut stages = Stages {
+        link_resolver: Arc::new(LinkResolver::new(&config.links)),
+        link_safety: Arc::new(LinkSafetyChecker::new(&config.link_safety, chain_classifier.domains())),
+        relevance_scorer: RelevanceScorer::new(&config.relevance),
+        chain_classifier,
+        cluster_index: ClusterIndex::new(&config.clustering, Vec::new()),
+    };
+    if config.clustering.enabled {
+        let open_clusters = cluster_repo.open_since(stages.cluster_index.window_start()).await?;
+        println!("Loaded {} open tweet clusters.", open_clusters.len());
+        stages.cluster_index.insert_clusters(open_clusters);
+    }
+
+    // 2. Pull the profiles that are due for a scrape, or every active profile for a backfill.
+    let profiles = match config.backfill.until {
+        Some(until) => {
+            println!("Backfill run: scrolling every active profile back to {}.", until);
+            profile_repo.active_profiles(&config.watchlist).await?
+        }
+        None => profile_repo.due_profiles(&config.watchlist).await?,
+    };
+    if profiles.is_empty() {
+        println!("No profiles are due for scraping. Add profiles to the '{}' collection.", config.database.profiles_collection);
+        return Ok(());
+    }
+    println!("{} profiles are due for scraping.", profiles.len());
+
+    // 3. Start the workers. They scrape on their own threads, since the browser API is blocking.
+    let total = profiles.len();
+    let (pool, mut outcomes) = ScraperPool::start(&config, profiles);
+
+    let mut completed = 0;
+    while let Some(PoolOutcome { profile, worker_id, attempts, result }) = outcomes.recv().await {
+        completed += 1;
+        println!(
+            "\n=== [{}/{}] @{} (priority {}, worker {:?}, {} attempts) ===",
+            completed, total, profile.handle, profile.priority, worker_id, attempts
+        );
+
+        // 4. Process and persist the batch, then 5. record the outcome on the profile.
+        let outcome = match result {
+            Ok(batch) => store_batch(&batch, &profile, &tweet_repo, &token_repo, &cluster_repo, &mut stages)
+                .await
+                .map(|()| {
+                    let previous = profile.last_seen_tweet_id.as_deref();
+                    if batch.left_gap(previous) {
+                        println!(
+                            "Warning: @{} posted more than {} tweets since tweet {}; keeping that watermark so the next run reads the rest.",
+                            profile.handle,
+                            batch.len(),
+                            previous.unwrap_or_default()
+                        );
                     }
+                    batch.next_watermark(previous).cloned()
+                }),
+            Err(e) => Err(e),
+        };
+        let bookkeeping = match &outcome {
+            Ok(watermark) => {
+                // The batch is stored, so an interrupted backfill no longer needs its checkpoint.
+                if let Some(until) = config.backfill.until {
+                    BackfillCheckpoint::new(&config.backfill.checkpoint_dir, &profile.handle, until).clear();
                 }
-                Err(e) => {
-                    eprintln!("Failed to process HTML into Airdrops: {}", e);
-                }
+                profile_repo.mark_scraped(&profile.handle, watermark.as_ref()).await
+            }
+            Err(e) => {
+                report_failure(&profile, e);
+                profile_repo.record_failure(&profile.handle, e).await
+            }
+        };
+        if let Err(e) = bookkeeping {
+            eprintln!("Failed to update profile @{}: {}", profile.handle, e);
+        }
+    }
+
+    let stats = tokio::task::spawn_blocking(move || pool.join()).await?;
+    if stats.halted {
+        eprintln!("\nThe scraper pool stopped early: the bot account is blocked or logged out and needs attention.");
+    }
+    println!(
+        "\nDone. {} profiles scraped, {} failed, {} retries, {} workers retired.",
+        stats.succeeded, stats.failed, stats.retried, stats.workers_retired
+    );
+    Ok(())
+}
+
+/// The processing stages every batch goes through before it is stored, set up once per run.
+struct Stages {
+    /// Expands the shortlinks the tweets left unexpanded.
+    link_resolver: Arc<LinkResolver>,
+    /// Flags phishing and drainer links.
+    link_safety: Arc<LinkSafetyChecker>,
+    /// Scores the records and drops the unrelated ones.
+    relevance_scorer: RelevanceScorer,
+    /// Attaches the chains the records are about.
+    chain_classifier: ChainClassifier,
+    /// Groups the records with their near-duplicates from earlier batches.
+    cluster_index: ClusterIndex,
+}
+
+/// Processes a scraped batch into `Airdrop` records, resolves their shortlinks, flags risky
+/// links, scores their relevance, classifies them by chain, clusters them with their
+/// near-duplicates, prints the relevant ones and persists them together with their token
+/// mentions and clusters.
+///
+/// # Returns
+/// `ScrapeResult<()>`: `Ok(())` once the records are stored, or the first processing or
+/// database error.
+async fn store_batch(
+    batch: &ScrapedBatch,
+    profile: &WatchedProfile,
+    tweet_repo: &TweetRepository,
+    token_repo: &TokenRepository,
+    cluster_repo: &ClusterRepository,
+    stages: &mut Stages,
+) -> ScrapeResult<()> {
+    println!(
+        "\nScraping complete. Found {} HTML posts and {} GraphQL responses (stopped: {:?}).",
+        batch.len(),
+        batch.graphql.len(),
+        batch.stats.stop_reason
+    );
+    println!("Processing the scraped content into Airdrop data...");
+
+    // Process the captured GraphQL responses and the collected raw HTML into structured `Airdrop` data.
+    let mut airdrops = GraphqlProcessor.process_batch(batch)?;
+
+    // Expand the shortlinks the tweets left unexpanded, flag the risky ones, then detect the
+    // blockchain addresses, the task checklist, the deadlines and the rewards in the text and
+    // the expanded links. Resolving blocks on HTTP requests.
+    let resolver = Arc::clone(&stages.link_resolver);
+    let link_safety = Arc::clone(&stages.link_safety);
+    let (profile_handle, profile_domains) = (profile.handle.clone(), profile.domains.clone());
+    let (mut airdrops, risky_links) = tokio::task::spawn_blocking(move || {
+        resolver.resolve_airdrops(&mut airdrops);
+        let risky_links = link_safety.check_airdrops(&mut airdrops, &profile_handle, &profile_domains);
+        addresses::detect_airdrops(&mut airdrops);
+        tasks::extract_airdrops(&mut airdrops);
+        deadlines::extract_airdrops(&mut airdrops);
+        rewards::extract_airdrops(&mut airdrops);
+        (airdrops, risky_links)
+    })
+    .await
+    .map_err(std::io::Error::other)?;
+    if risky_links > 0 {
+        println!("Flagged {} risky links.", risky_links);
+    }
+
+    // Score the records and drop those unrelated to airdrops before they are stored.
+    let dropped = stages.relevance_scorer.score_airdrops(&mut airdrops);
+    if dropped > 0 {
+        println!("Dropped {} records below the relevance threshold.", dropped);
+    }
+    stages.chain_classifier.classify_airdrops(&mut airdrops, &profile.chains);
+
+    // Group the records with their near-duplicates. Clusters of re-scraped tweets that are no
+    // longer open are looked up first, so those tweets keep their cluster. The records are
+    // assigned on a copy of the index, which replaces it only once they and their clusters
+    // are stored, so a failed write leaves no cluster members that were never saved.
+    let mut cluster_index = stages.cluster_index.clone();
+    let unknown = cluster_index.unknown_tweet_ids(&airdrops);
+    cluster_index.insert_clusters(cluster_repo.containing(&unknown).await?);
+    let clusters = cluster_index.assign_airdrops(&mut airdrops);
+
+    println!("\nProcessed {} Airdrop records:", airdrops.len());
+    for (i, airdrop) in airdrops.iter().enumerate() {
+        println!("\n{}. Tweet ID: {}", i + 1, airdrop.tweetId);
+        println!("    Text       : {}", airdrop.text.as_deref().unwrap_or("No text"));
+        println!("    Author ID  : {}", airdrop.authorId.as_deref().unwrap_or("Unknown"));
+        println!("    Type       : {:?}", airdrop.tweetType);
+        if let Some(relevance) = &airdrop.relevance {
+            println!("    Relevance  : {:?}, score {} ({:?})", relevance.label, relevance.score, relevance.matched_rules);
+        }
+        if let Some(reposted_by) = &airdrop.repostedBy {
+            println!("    Reposted By: @{}", reposted_by);
+        }
+        if let Some(quoted) = &airdrop.quotedTweet {
+            println!(
+                "    Quotes     : @{} ({}): {}",
+                quoted.author_handle.as_deref().unwrap_or("unknown"),
+                quoted.tweet_id.as_deref().unwrap_or("unknown id"),
+                quoted.text.as_deref().unwrap_or("No text")
+            );
+        }
+        println!("    Created At : {:?}", airdrop.createdAt);
+        println!("    Mentions   : {:?}", airdrop.mentionedUsers);
+        println!("    Tags       : {:?} {:?}", airdrop.cashtags, airdrop.hashtags);
+        println!("    Keywords   : {:?} (token: {:?})", airdrop.keywords, airdrop.tokenName);
+        for link in &airdrop.resolvedLinks {
+            println!("    Link       : {} -> {} ({})", link.short, link.url(), link.domain.as_deref().unwrap_or("unresolved"));
+            if !link.risk_flags.is_empty() {
+                println!("    Risk       : {:?}", link.risk_flags);
             }
         }
-        Err(e) => {
-            eprintln!("Scraping failed: {}", e);
+        for address in &airdrop.chainAddresses {
+            println!("    Address    : {} ({:?} on {}, from {:?})", address.address, address.kind, address.chain, address.source);
+        }
+        for (step, task) in airdrop.tasks.iter().enumerate() {
+            println!("    Task {:<6}: {:?} {}", step + 1, task.kind, task.target.as_deref().unwrap_or(""));
+        }
+        for deadline in &airdrop.deadlines {
+            println!("    Deadline   : {:?} at {} (\"{}\")", deadline.kind, deadline.at, deadline.expression);
         }
+        for chain in &airdrop.chains {
+            println!("    Chain      : {} ({:.2}, {:?})", chain.name, chain.confidence, chain.signals);
+        }
+        for reward in &airdrop.rewards {
+            println!("    Reward     : {} {:?} {} (\"{}\")", reward.amount, reward.unit, reward.token.as_deref().unwrap_or(""), reward.expression);
+        }
+        if let Some(media) = &airdrop.media {
+            for item in &media.items {
+                println!("    Media      : {:?} {} (alt: {:?})", item.kind, item.url, item.alt_text);
+            }
+            if let Some(card) = &media.card {
+                println!("    Card       : {:?} - {:?} ({:?})", card.title, card.description, card.domain);
+            }
+        }
+        if let Some(engagement) = &airdrop.engagement {
+            println!(
+                "    Engagement : {:?} likes, {:?} reposts, {:?} replies, {:?} views, {:?} bookmarks",
+                engagement.likes, engagement.reposts, engagement.replies, engagement.views, engagement.bookmarks
+            );
+        }
+        if let (Some(root), Some(position)) = (&airdrop.threadRootId, airdrop.threadPosition) {
+            println!("    Thread     : position {} of thread {}", position, root);
+        }
+        if let Some(cluster_id) = &airdrop.clusterId {
+            println!("    Cluster    : {}", cluster_id);
+        }
+        println!("----------------------------------------------------");
     }
+    for thread in &batch.threads {
+        if let Some(text) = thread_text(&airdrops, &thread.root_tweet_id) {
+            println!("\nThread {} ({} tweets):\n{}", thread.root_tweet_id, thread.tweet_ids.len(), text);
+        }
+    }
+
+    // Persist the records, merging with anything the level-1 bot already stored.
+    let summary = tweet_repo.upsert_each(&airdrops).await?;
+    println!(
+        "\nStored Airdrop records: {} inserted, {} updated, {} unchanged.",
+        summary.inserted, summary.updated, summary.unchanged
+    );
+
+    // Link the mentioned tokens to their project entities.
+    let entities = token_repo.record_airdrops(&airdrops, &stages.chain_classifier).await?;
+    println!("Linked {} token mentions to their entities.", entities.len());
+
+    // Save the clusters the records opened or joined.
+    cluster_repo.save(&clusters).await?;
+    println!("Updated {} tweet clusters.", clusters.len());
+    stages.cluster_index = cluster_index;
 
-    println!("\nDone.");
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Prints a profile failure together with its classification.
+fn report_failure(profile: &WatchedProfile, error: &errors::ScrapeError) {
+    eprintln!(
+        "Scraping @{} failed ({:?}, recommended action: {:?}): {}",
+        profile.handle,
+        error.class(),
+        error.recovery(),
+        error
+    );
+}
@@ -0,0 +1,323 @@
+//! This module detects the blockchain addresses an airdrop tweet points at: claim contracts,
+//! token mints and wallets, written in the text or embedded in the expanded links (explorer,
+//! DEX and launchpad pages).
+//!
+//! Three kinds are recognized:
+//! - Solana base58 public keys: 32 to 44 characters of the base58 alphabet that decode to
+//!   exactly 32 bytes, which rules out ordinary words and transaction signatures.
+//! - EVM `0x` addresses: 40 hex digits. Mixed-case addresses must match their EIP-55
+//!   checksum; all-lower and all-upper ones carry none and are accepted. Every address is
+//!   stored in its checksummed form, so the same address is recorded once.
+//! - ENS (`name.eth`) and Solana Name Service (`name.sol`) names.
+//!
+//! The chain is only a guess: the explorer a link points at, a chain named in the tweet, or
+//! the chain the address format belongs to.
+
+use crate::scraper::user_profile::Airdrop;
+use crate::utils::is_host;
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+use url::Url;
+
+/// The format of a detected address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressKind {
+    /// A Solana base58 public key (wallet, program or token mint).
+    Solana,
+    /// A `0x` EVM address (wallet or contract).
+    Evm,
+    /// An ENS name, e.g. `vitalik.eth`.
+    Ens,
+    /// A Solana Name Service name, e.g. `bonfida.sol`.
+    Sns,
+}
+
+/// Where an address was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressSource {
+    /// The tweet text.
+    Text,
+    /// One of the tweet's expanded links.
+    Link,
+}
+
+/// A blockchain address mentioned by a tweet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainAddress {
+    /// The address: base58 as written, EVM in its EIP-55 checksummed form, names lower-cased.
+    pub address: String,
+    /// The format of the address.
+    pub kind: AddressKind,
+    /// The chain the address most likely belongs to, e.g. `solana`, `ethereum` or `base`;
+    /// `evm` when an EVM address gives no hint of its chain.
+    pub chain: String,
+    /// Where the address was found.
+    pub source: AddressSource,
+}
+
+/// Explorer and DEX hosts that only serve one chain, with that chain.
+const CHAIN_HOSTS: [(&str, &str); 16] = [
+    ("solscan.io", "solana"),
+    ("solana.fm", "solana"),
+    ("explorer.solana.com", "solana"),
+    ("pump.fun", "solana"),
+    ("jup.ag", "solana"),
+    ("raydium.io", "solana"),
+    ("etherscan.io", "ethereum"),
+    ("basescan.org", "base"),
+    ("arbiscan.io", "arbitrum"),
+    ("optimistic.etherscan.io", "optimism"),
+    ("polygonscan.com", "polygon"),
+    ("bscscan.com", "bsc"),
+    ("snowtrace.io", "avalanche"),
+    ("lineascan.build", "linea"),
+    ("era.zksync.network", "zksync"),
+    ("blastscan.io", "blast"),
+];
+
+/// Words that name an EVM chain in the tweet text, lower-cased, with the chain.
+/// "Base" is matched separately, since the lower-case word is too common.
+const CHAIN_WORDS: [(&str, &str); 10] = [
+    ("ethereum", "ethereum"),
+    ("arbitrum", "arbitrum"),
+    ("optimism", "optimism"),
+    ("polygon", "polygon"),
+    ("bsc", "bsc"),
+    ("bnb", "bsc"),
+    ("avalanche", "avalanche"),
+    ("linea", "linea"),
+    ("zksync", "zksync"),
+    ("blast", "blast"),
+];
+
+/// Detects the addresses of every record in its text and its (expanded) `links`. Run this
+/// after the shortlinks are resolved, so addresses in their destinations are found too.
+pub fn detect_airdrops(airdrops: &mut [Airdrop]) {
+    for airdrop in airdrops.iter_mut() {
+        airdrop.chainAddresses = find_addresses(airdrop.text.as_deref().unwrap_or_default(), &airdrop.links);
+    }
+}
+
+/// Finds the addresses in `text` and in `links`, in order of appearance and without
+/// duplicates; an address in both is recorded as found in the text.
+pub fn find_addresses(text: &str, links: &[String]) -> Vec<ChainAddress> {
+    let text_chain = evm_chain_in_text(text);
+    let mut found: Vec<ChainAddress> = Vec::new();
+
+    // URLs in the text are read from `links`, where they are expanded.
+    let words = text.split_whitespace().filter(|word| !word.contains("://") && !word.starts_with("www."));
+    for token in words.flat_map(|word| word.split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')))) {
+        if let Some(address) = parse_token(token, AddressSource::Text, text_chain) {
+            push_unique(&mut found, address);
+        }
+    }
+
+    for link in links {
+        let Ok(url) = Url::parse(link) else {
+            continue;
+        };
+        let host_chain = url.host_str().and_then(chain_of_host);
+        let evm_chain = host_chain.filter(|chain| *chain != "solana").or(text_chain);
+        // Path segments and query values, split further on separators such as the `-` of
+        // `jup.ag/swap/SOL-<mint>`.
+        let path = url.path().to_string();
+        let query = url.query().unwrap_or_default().to_string();
+        for token in path.split('/').chain(query.split('&').filter_map(|pair| pair.split('=').nth(1))) {
+            let address = parse_token(token, AddressSource::Link, evm_chain)
+                .or_else(|| token.split(['-', '_', '.']).find_map(|part| parse_token(part, AddressSource::Link, evm_chain)));
+            if let Some(address) = address {
+                push_unique(&mut found, address);
+            }
+        }
+    }
+    found
+}
+
+/// Classifies a single token, trimmed of surrounding punctuation, as an address.
+fn parse_token(token: &str, source: AddressSource, evm_chain: Option<&str>) -> Option<ChainAddress> {
+    let token = token.trim_matches(|c: char| matches!(c, '.' | '-' | '_'));
+    let (address, kind, chain) = if let Some(hex) = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")) {
+        (checksummed_evm_address(hex)?, AddressKind::Evm, evm_chain.unwrap_or("evm"))
+    } else if let Some(name) = name_service(&token.to_ascii_lowercase(), ".eth", 3) {
+        (name, AddressKind::Ens, "ethereum")
+    } else if let Some(name) = name_service(token, ".sol", 1) {
+        // Not lower-cased: capitalized tokens such as `Token.sol` are Solidity source files.
+        (name, AddressKind::Sns, "solana")
+    } else if is_solana_pubkey(token) {
+        (token.to_string(), AddressKind::Solana, "solana")
+    } else {
+        return None;
+    };
+    Some(ChainAddress {
+        address,
+        kind,
+        chain: chain.to_string(),
+        source,
+    })
+}
+
+/// Returns `true` if `token` is a Solana public key: 32 to 44 base58 characters decoding to
+/// exactly 32 bytes.
+fn is_solana_pubkey(token: &str) -> bool {
+    (32..=44).contains(&token.len()) && bs58::decode(token).into_vec().is_ok_and(|bytes| bytes.len() == 32)
+}
+
+/// Returns the `0x` address for 40 hex digits in EIP-55 checksummed form.
+///
+/// # Returns
+/// `Option<String>`: `None` if `hex` is not 40 hex digits, or is mixed-case with a checksum
+/// that does not match (a typo or a tampered address).
+fn checksummed_evm_address(hex: &str) -> Option<String> {
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let lower = hex.to_ascii_lowercase();
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(lower.as_bytes());
+    keccak.finalize(&mut hash);
+
+    // A letter is upper-cased when the matching nibble of the hash is 8 or more.
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(index, c)| {
+            let nibble = if index % 2 == 0 { hash[index / 2] >> 4 } else { hash[index / 2] & 0x0f };
+            if nibble >= 8 { c.to_ascii_uppercase() } else { c }
+        })
+        .collect();
+
+    let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case && hex != checksummed {
+        return None;
+    }
+    Some(format!("0x{}", checksummed))
+}
+
+/// Returns the name if `token` is a name under `suffix` whose labels are lower-case letters,
+/// digits and hyphens and whose label directly under `suffix` has at least `min_length`
+/// characters.
+fn name_service(token: &str, suffix: &str, min_length: usize) -> Option<String> {
+    let name = token.strip_suffix(suffix)?;
+    let labels_valid = name
+        .split('.')
+        .all(|label| !label.is_empty() && label.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'));
+    let label = name.rsplit('.').next().unwrap_or_default();
+    (labels_valid && label.len() >= min_length).then(|| token.to_string())
+}
+
+/// Returns the chain served by an explorer or DEX host.
+fn chain_of_host(host: &str) -> Option<&'static str> {
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    CHAIN_HOSTS
+        .iter()
+        .filter(|(known, _)| is_host(host, known))
+        // The longest match wins, so `optimistic.etherscan.io` is not read as Ethereum.
+        .max_by_key(|(known, _)| known.len())
+        .map(|&(_, chain)| chain)
+}
+
+/// Returns the EVM chain the tweet text names, if any.
+fn evm_chain_in_text(text: &str) -> Option<&'static str> {
+    text.split(|c: char| !c.is_alphanumeric()).find_map(|word| {
+        if word == "Base" {
+            return Some("base");
+        }
+        let word = word.to_lowercase();
+        CHAIN_WORDS.iter().find(|(name, _)| word == *name).map(|&(_, chain)| chain)
+    })
+}
+
+/// Appends `address` unless the same address was already found.
+fn push_unique(found: &mut Vec<ChainAddress>, address: ChainAddress) {
+    if !found.iter().any(|known| known.address == address.address) {
+        found.push(address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_evm_addresses() {
+        // The test vectors of EIP-55.
+        let vectors = [
+            "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "fB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "dbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "D1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ];
+        for vector in vectors {
+            assert_eq!(checksummed_evm_address(vector), Some(format!("0x{}", vector)));
+            assert_eq!(checksummed_evm_address(&vector.to_ascii_lowercase()), Some(format!("0x{}", vector)));
+            assert_eq!(checksummed_evm_address(&vector.to_ascii_uppercase()), Some(format!("0x{}", vector)));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_evm_addresses() {
+        let cases = [
+            // The first EIP-55 vector with the case of its last letter flipped.
+            "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD",
+            "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAe",
+            "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAedd",
+            "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg",
+        ];
+        for hex in cases {
+            assert_eq!(checksummed_evm_address(hex), None, "{}", hex);
+        }
+    }
+
+    #[test]
+    fn validates_solana_pubkeys() {
+        let cases = [
+            ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", true),
+            ("11111111111111111111111111111111", true),
+            // A 64-byte transaction signature.
+            ("3puPhtso1JoRW2YhVCCVoJPR3t6DtXCq821yKZvBzb6nCBD32bNgpW9HMzMaxL929PybYkXUGayTuW4hsHB6xnac", false),
+            // 44 characters that decode to 33 bytes.
+            ("zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz", false),
+            // `0`, `O`, `I` and `l` are not in the base58 alphabet.
+            ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt10", false),
+            ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDtl", false),
+            ("Airdrop", false),
+        ];
+        for (token, expected) in cases {
+            assert_eq!(is_solana_pubkey(token), expected, "{}", token);
+        }
+    }
+
+    #[test]
+    fn finds_addresses_in_text_and_links() {
+        let found = |text: &str, links: &[&str]| -> Vec<(String, AddressKind, String)> {
+            let links: Vec<String> = links.iter().map(|link| link.to_string()).collect();
+            find_addresses(text, &links).into_iter().map(|address| (address.address, address.kind, address.chain)).collect()
+        };
+        let address = |address: &str, kind, chain: &str| (address.to_string(), kind, chain.to_string());
+
+        assert_eq!(found("Deployed Token.sol and Vault.sol", &[]), Vec::new());
+        assert_eq!(found("Claim at bonfida.sol", &[]), vec![address("bonfida.sol", AddressKind::Sns, "solana")]);
+        assert_eq!(found("Send to Vitalik.eth", &[]), vec![address("vitalik.eth", AddressKind::Ens, "ethereum")]);
+        assert_eq!(
+            found("Claim contract on Arbitrum: 0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", &[]),
+            vec![address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", AddressKind::Evm, "arbitrum")]
+        );
+        assert_eq!(found("Typo: 0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD", &[]), Vec::new());
+        assert_eq!(
+            found("Tx 3puPhtso1JoRW2YhVCCVoJPR3t6DtXCq821yKZvBzb6nCBD32bNgpW9HMzMaxL929PybYkXUGayTuW4hsHB6xnac", &[]),
+            Vec::new()
+        );
+        assert_eq!(
+            found("Swap now", &["https://jup.ag/swap/SOL-EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"]),
+            vec![address("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", AddressKind::Solana, "solana")]
+        );
+        assert_eq!(
+            found("Token", &["https://basescan.org/token/0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359"]),
+            vec![address("0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359", AddressKind::Evm, "base")]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! This module classifies records by the chains and ecosystems they are about (Solana,
+//! Ethereum, Base, Arbitrum, Sui, ...), so they can be filtered by chain.
+//!
+//! `ChainClassifier` reads the chains from a data file (`data/chains.toml`, built into the
+//! binary, or the file `chains.data_path` points at) that maps keywords, cashtags, link domains
+//! and X handles to each chain. A record collects weighted signals per chain: the addresses
+//! detected on it, links to its explorers and dapps, the author being one of its accounts (or
+//! the profile being tagged with it), mentions of its accounts, its cashtags and its keywords.
+//! A chain's confidence is its score over the saturation score of the data file, capped at 1.
+
+use crate::config::ChainsConfig;
+use crate::scraper::user_profile::Airdrop;
+use crate::utils::is_host;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+
+/// The chain data built into the binary.
+const BUILTIN_CHAINS: &str = include_str!("../../data/chains.toml");
+
+/// A chain a record is about.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainMatch {
+    /// Id of the chain in the data file, e.g. `solana` or `base`.
+    pub chain: String,
+    /// Display name of the chain, e.g. `BNB Chain`.
+    pub name: String,
+    /// How sure the classifier is, from 0 to 1.
+    pub confidence: f64,
+    /// The signals that pointed at the chain, e.g. `domain:jup.ag` or `cashtag:$SOL`.
+    pub signals: Vec<String>,
+}
+
+/// The contents of a chain data file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChainData {
+    weights: SignalWeights,
+    #[serde(rename = "chain")]
+    chains: Vec<ChainEntry>,
+}
+
+/// What each kind of signal adds to a chain's score.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignalWeights {
+    address: f64,
+    domain: f64,
+    author: f64,
+    mention: f64,
+    cashtag: f64,
+    keyword: f64,
+    /// The score at which the confidence reaches 1.
+    saturation: f64,
+}
+
+/// A chain and the signals that point at it.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChainEntry {
+    id: String,
+    name: String,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    cashtags: Vec<String>,
+    #[serde(default)]
+    domains: Vec<String>,
+    #[serde(default)]
+    handles: Vec<String>,
+}
+
+/// Classifies records by chain from the signals of a chain data file.
+pub struct ChainClassifier {
+    weights: SignalWeights,
+    chains: Vec<ChainEntry>,
+    min_confidence: f64,
+}
+
+impl ChainClassifier {
+    /// Loads the chain data file named by the `[chains]` settings, or the built-in one.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed, names a chain twice, or has a
+    /// negative weight or a saturation that is not positive.
+    pub fn load(config: &ChainsConfig) -> Result<Self> {
+        let data: ChainData = match &config.data_path {
+            Some(path) => {
+                let contents = fs::read_to_string(path).with_context(|| format!("Failed to read chain data file {:?}", path))?;
+                toml::from_str(&contents).with_context(|| format!("Failed to parse chain data file {:?}", path))?
+            }
+            None => toml::from_str(BUILTIN_CHAINS).context("Failed to parse the built-in chain data")?,
+        };
+
+        let weights = data.weights;
+        let all_weights = [weights.address, weights.domain, weights.author, weights.mention, weights.cashtag, weights.keyword];
+        if all_weights.iter().any(|weight| *weight < 0.0) || weights.saturation <= 0.0 {
+            return Err(anyhow!("Chain data weights must not be negative and the saturation must be positive"));
+        }
+        let mut ids = HashSet::new();
+        if let Some(duplicate) = data.chains.iter().find(|chain| !ids.insert(chain.id.as_str())) {
+            return Err(anyhow!("Chain {:?} is listed twice in the chain data", duplicate.id));
+        }
+
+        // Normalize once: lower-case keywords, domains and handles, upper-case cashtags.
+        let lowercase = |values: Vec<String>| values.into_iter().map(|value| value.trim().trim_start_matches('@').to_lowercase()).collect();
+        let chains = data
+            .chains
+            .into_iter()
+            .map(|chain| ChainEntry {
+                keywords: lowercase(chain.keywords),
+                cashtags: chain.cashtags.iter().map(|tag| tag.trim().trim_start_matches('$').to_uppercase()).collect(),
+                domains: lowercase(chain.domains),
+                handles: lowercase(chain.handles),
+                ..chain
+            })
+            .collect();
+        Ok(Self {
+            weights,
+            chains,
+            min_confidence: config.min_confidence,
+        })
+    }
+
+    /// Returns the explorer and dapp domains of every chain.
+    pub fn domains(&self) -> impl Iterator<Item = &str> {
+        self.chains.iter().flat_map(|chain| chain.domains.iter().map(String::as_str))
+    }
+
+    /// Returns `true` if `handle`, lower-cased and without the `@`, is an account of a chain or
+    /// one of its core projects in the chain data.
+    pub fn is_listed_handle(&self, handle: &str) -> bool {
+        self.chains.iter().any(|chain| chain.handles.iter().any(|known| known == handle))
+    }
+
+    /// Sets `chains` on every record.
+    ///
+    /// # Arguments
+    /// * `airdrops` - The records, with their addresses detected and shortlinks resolved.
+    /// * `profile_chains` - The chain ids the scraped profile is tagged with.
+    pub fn classify_airdrops(&self, airdrops: &mut [Airdrop], profile_chains: &[String]) {
+        for airdrop in airdrops.iter_mut() {
+            airdrop.chains = self.classify(airdrop, profile_chains);
+        }
+    }
+
+    /// Classifies a single record.
+    ///
+    /// # Returns
+    /// `Vec<ChainMatch>`: The chains at or above the minimum confidence, most confident first.
+    pub fn classify(&self, airdrop: &Airdrop, profile_chains: &[String]) -> Vec<ChainMatch> {
+        let text = format!(
+            "{}\n{}",
+            airdrop.text.as_deref().unwrap_or_default(),
+            airdrop.quotedTweet.as_ref().and_then(|quoted| quoted.text.as_deref()).unwrap_or_default()
+        )
+        .to_lowercase();
+        let author = airdrop.authorHandle.as_deref().map(|handle| handle.trim_start_matches('@').to_lowercase());
+        let domains: Vec<&str> = airdrop.resolvedLinks.iter().filter_map(|link| link.domain.as_deref()).collect();
+
+        let mut matches: Vec<ChainMatch> = self
+            .chains
+            .iter()
+            .filter_map(|chain| {
+                let mut signals: Vec<(String, f64)> = Vec::new();
+                let mut add = |signal: String, weight: f64| {
+                    if !signals.iter().any(|(known, _)| *known == signal) {
+                        signals.push((signal, weight));
+                    }
+                };
+
+                // Addresses whose chain is only known as "evm" say nothing about the chain.
+                for address in airdrop.chainAddresses.iter().filter(|address| address.chain == chain.id) {
+                    add(format!("address:{}", address.address), self.weights.address);
+                }
+                for domain in &domains {
+                    if let Some(known) = chain.domains.iter().find(|known| is_host(domain, known)) {
+                        add(format!("domain:{}", known), self.weights.domain);
+                    }
+                }
+                // The profile's own ecosystem only says something about its own tweets, not reposts.
+                let author_is_chain = author.as_deref().is_some_and(|author| chain.handles.iter().any(|handle| handle == author));
+                let profile_is_chain = airdrop.repostedBy.is_none() && profile_chains.iter().any(|id| id.eq_ignore_ascii_case(&chain.id));
+                if author_is_chain || profile_is_chain {
+                    add(format!("author:@{}", author.as_deref().unwrap_or("profile")), self.weights.author);
+                }
+                for mention in &airdrop.mentionedUsers {
+                    let handle = mention.trim_start_matches('@').to_lowercase();
+                    if author.as_deref() != Some(handle.as_str()) && chain.handles.contains(&handle) {
+                        add(format!("mention:@{}", handle), self.weights.mention);
+                    }
+                }
+                for cashtag in &airdrop.cashtags {
+                    let ticker = cashtag.trim_start_matches('$').to_uppercase();
+                    if chain.cashtags.contains(&ticker) {
+                        add(format!("cashtag:${}", ticker), self.weights.cashtag);
+                    }
+                }
+                for keyword in chain.keywords.iter().filter(|keyword| contains_phrase(&text, keyword)) {
+                    add(format!("keyword:{}", keyword), self.weights.keyword);
+                }
+
+                let score: f64 = signals.iter().map(|(_, weight)| weight).sum();
+                let confidence = (score / self.weights.saturation).min(1.0);
+                (score > 0.0 && confidence >= self.min_confidence).then(|| ChainMatch {
+                    chain: chain.id.clone(),
+                    name: chain.name.clone(),
+                    // Two decimals are all a filter needs.
+                    confidence: (confidence * 100.0).round() / 100.0,
+                    signals: signals.into_iter().map(|(signal, _)| signal).collect(),
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        matches
+    }
+}
+
+/// Returns `true` if `phrase` occurs in `text` as whole words.
+fn contains_phrase(text: &str, phrase: &str) -> bool {
+    !phrase.is_empty()
+        && text.match_indices(phrase).any(|(index, _)| {
+            let before = text[..index].chars().next_back();
+            let after = text[index + phrase.len()..].chars().next();
+            before.is_none_or(|c| !c.is_alphanumeric()) && after.is_none_or(|c| !c.is_alphanumeric())
+        })
+}
@@ -0,0 +1,137 @@
+//! This module classifies a tweet collected from a profile timeline as an original post, a
+//! repost, a reply, a quote or a pinned post, and records the accounts and tweets it relates
+//! to, so that airdrop announcements are credited to the account that actually wrote them.
+//!
+//! `TweetRelations::from_document` reads the rendered HTML; the GraphQL processor fills the
+//! same struct from the exact fields of the timeline JSON.
+
+use crate::errors::ScrapeResult;
+use crate::processing::quoted_tweet::quote_card;
+use crate::scraper::timeline::TimelineEntry;
+use crate::utils::{handle_from_href, parse_selector, tweet_id_from_href};
+use scraper::{ElementRef, Html};
+use serde::{Deserialize, Serialize};
+
+/// What a timeline item is. When several apply, the first one listed wins: a reposted reply
+/// is a `Repost`, a pinned quote is `Pinned`; the related ids are recorded regardless.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TweetKind {
+    /// Another account's tweet, shared by the profile.
+    Repost,
+    /// The tweet the profile pinned to the top of its timeline.
+    Pinned,
+    /// A reply to another tweet.
+    Reply,
+    /// A tweet quoting another tweet.
+    Quote,
+    /// A regular tweet written by the profile.
+    Original,
+}
+
+/// The classification of a tweet and the accounts and tweets it relates to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TweetRelations {
+    /// What the timeline item is.
+    pub kind: TweetKind,
+    /// Handle of the account that wrote the tweet, without the leading `@`.
+    pub author_handle: Option<String>,
+    /// Handle of the account that reposted it, for reposts.
+    pub reposted_by: Option<String>,
+    /// Id of the tweet this one replies to.
+    pub in_reply_to_tweet_id: Option<String>,
+    /// Handle of the account this tweet replies to.
+    pub in_reply_to_handle: Option<String>,
+    /// Id of the tweet this one quotes.
+    pub quoted_tweet_id: Option<String>,
+}
+
+impl TweetRelations {
+    /// Derives `kind` from the relations, given whether the timeline shows the tweet as
+    /// pinned and whether it quotes another tweet.
+    pub fn classified(mut self, pinned: bool, is_quote: bool) -> Self {
+        self.kind = if self.reposted_by.is_some() {
+            TweetKind::Repost
+        } else if pinned {
+            TweetKind::Pinned
+        } else if self.in_reply_to_tweet_id.is_some() || self.in_reply_to_handle.is_some() {
+            TweetKind::Reply
+        } else if is_quote || self.quoted_tweet_id.is_some() {
+            TweetKind::Quote
+        } else {
+            TweetKind::Original
+        };
+        self
+    }
+
+    /// Classifies a parsed tweet `article`.
+    ///
+    /// * A social context line that links to a profile ("Alice reposted") marks a repost by
+    ///   that profile; one without a link ("Pinned") marks the pinned tweet.
+    /// * The author is read from the first `User-Name` block; a second one belongs to a
+    ///   quoted tweet.
+    /// * The "Replying to @handle" line marks a reply. The rendered HTML does not carry the
+    ///   replied-to tweet id; it comes from the GraphQL responses when they were captured.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+    pub fn from_document(document: &Html, entry: &TimelineEntry) -> ScrapeResult<Self> {
+        let social_context_selector = parse_selector("[data-testid='socialContext']")?;
+        let user_name_selector = parse_selector("div[data-testid='User-Name']")?;
+        let profile_link_selector = parse_selector("a[href^='/']")?;
+        let status_link_selector = parse_selector("a[href*='/status/']")?;
+        let div_selector = parse_selector("div")?;
+
+        let mut reposted_by = None;
+        let mut pinned = false;
+        if let Some(context) = document.select(&social_context_selector).next() {
+            let link = context
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .find(|ancestor| ancestor.value().name() == "a");
+            match link.and_then(|link| link.value().attr("href")).and_then(handle_from_href) {
+                Some(handle) => reposted_by = Some(handle),
+                None => pinned = true,
+            }
+        }
+
+        let user_names: Vec<ElementRef> = document.select(&user_name_selector).collect();
+        let author_handle = user_names.first().and_then(|user_name| {
+            user_name
+                .select(&profile_link_selector)
+                .filter_map(|link| link.value().attr("href"))
+                .find_map(handle_from_href)
+        });
+
+        // A second `User-Name` belongs to the quoted tweet.
+        let is_quote = user_names.len() > 1;
+        let quoted_tweet_id = quote_card(document)?.and_then(|card| {
+            card.select(&status_link_selector)
+                .filter_map(|link| link.value().attr("href"))
+                .filter_map(tweet_id_from_href)
+                .find(|id| Some(id) != entry.tweet_id.as_ref())
+        });
+
+        // "Replying to @handle": a block whose own text starts the line, followed by the link.
+        let in_reply_to_handle = document
+            .select(&div_selector)
+            .find(|div| {
+                div.children()
+                    .filter_map(|child| child.value().as_text())
+                    .any(|text| text.trim().starts_with("Replying to"))
+            })
+            .and_then(|div| div.select(&profile_link_selector).next())
+            .and_then(|link| link.value().attr("href"))
+            .and_then(handle_from_href);
+
+        let relations = Self {
+            kind: TweetKind::Original,
+            author_handle,
+            reposted_by,
+            in_reply_to_tweet_id: None,
+            in_reply_to_handle,
+            quoted_tweet_id,
+        };
+        Ok(relations.classified(pinned, is_quote))
+    }
+}
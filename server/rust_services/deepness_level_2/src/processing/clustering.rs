@@ -0,0 +1,286 @@
+//! This module groups tweets that announce the same opportunity into clusters, across authors
+//! and batches.
+//!
+//! The same airdrop is reposted by dozens of aggregator accounts with slightly different
+//! wording. Every record gets a fingerprint: a 64-bit SimHash over the distinct words of its
+//! normalized text (lower-cased, without URLs, mentions, punctuation and stop words, the
+//! quoted tweet included), its links (host and path) and its cashtags. Word order and word
+//! pairs are left out on purpose: rewording shuffles them, and short tweets have too few
+//! words for them to average out. A record joins the open cluster it is closest to when:
+//! - its SimHash differs from a member's by at most `max_distance` bits, or
+//! - it shares a link with the cluster and differs by at most twice that, or
+//! - its text is too short for a SimHash and it shares both a link and a cashtag.
+//!
+//! Otherwise it opens a new cluster, named after its `tweetId`. The earliest tweet of a
+//! cluster is its canonical record. Clusters are loaded from and saved to the `clusters`
+//! collection, and stay open for `window_days` after their last tweet.
+
+use crate::config::ClusteringConfig;
+use crate::db::cluster_repo::TweetCluster;
+use crate::scraper::user_profile::Airdrop;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use url::Url;
+
+/// Most fingerprints kept per cluster; later members are matched against the first ones.
+const MAX_FINGERPRINTS: usize = 32;
+
+/// Most links kept per cluster.
+const MAX_LINKS: usize = 32;
+
+/// Words too common to tell two announcements apart.
+const STOP_WORDS: [&str; 24] = [
+    "a", "an", "the", "and", "or", "to", "of", "in", "on", "at", "by", "for", "with", "via", "is", "are", "be", "it", "its",
+    "this", "your", "our", "now", "before",
+];
+
+/// What a record is compared by.
+struct Fingerprint {
+    /// SimHash of the normalized text, `None` below `min_words` distinct words.
+    simhash: Option<u64>,
+    /// Normalized links with a path, e.g. `galxe.com/project/campaign/GC1`.
+    links: Vec<String>,
+    /// Cashtags, upper-cased.
+    cashtags: Vec<String>,
+}
+
+/// The clusters open for new tweets, and the clusters of the tweets seen so far.
+#[derive(Clone)]
+pub struct ClusterIndex {
+    enabled: bool,
+    max_distance: u32,
+    min_words: usize,
+    window: Duration,
+    clusters: Vec<TweetCluster>,
+    /// Cluster position by cluster id.
+    by_id: HashMap<String, usize>,
+    /// Cluster position by member tweet id.
+    by_tweet: HashMap<String, usize>,
+}
+
+impl ClusterIndex {
+    /// Creates an index from the `[clustering]` settings and the stored open clusters.
+    pub fn new(config: &ClusteringConfig, clusters: Vec<TweetCluster>) -> Self {
+        let mut index = Self {
+            enabled: config.enabled,
+            max_distance: config.max_distance,
+            min_words: config.min_words,
+            window: Duration::days(i64::from(config.window_days)),
+            clusters: Vec::new(),
+            by_id: HashMap::new(),
+            by_tweet: HashMap::new(),
+        };
+        index.insert_clusters(clusters);
+        index
+    }
+
+    /// Returns the start of the window: clusters a tweet joined since then are open.
+    pub fn window_start(&self) -> DateTime<Utc> {
+        Utc::now() - self.window
+    }
+
+    /// Returns the ids of the records whose cluster is not loaded, to look up in the
+    /// database before `assign_airdrops`. Empty with clustering disabled.
+    pub fn unknown_tweet_ids(&self, airdrops: &[Airdrop]) -> Vec<String> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        airdrops
+            .iter()
+            .map(|airdrop| &airdrop.tweetId)
+            .filter(|tweet_id| !self.by_tweet.contains_key(*tweet_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Adds stored clusters to the index, skipping those already loaded.
+    pub fn insert_clusters(&mut self, clusters: Vec<TweetCluster>) {
+        for cluster in clusters {
+            if self.by_id.contains_key(&cluster.cluster_id) {
+                continue;
+            }
+            let position = self.clusters.len();
+            self.by_id.insert(cluster.cluster_id.clone(), position);
+            for tweet_id in &cluster.tweet_ids {
+                self.by_tweet.insert(tweet_id.clone(), position);
+            }
+            self.clusters.push(cluster);
+        }
+    }
+
+    /// Sets `clusterId` on every record, adding it to the closest open cluster or opening a
+    /// new one. Records that already belong to a cluster keep it. Records are placed from the
+    /// earliest to the latest, so the tweet that opens a cluster is its earliest.
+    ///
+    /// # Returns
+    /// `Vec<TweetCluster>`: The clusters that were opened or joined, to be saved.
+    pub fn assign_airdrops(&mut self, airdrops: &mut [Airdrop]) -> Vec<TweetCluster> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let now = Utc::now();
+        let mut order: Vec<usize> = (0..airdrops.len()).collect();
+        order.sort_by_key(|&index| airdrops[index].createdAt.unwrap_or(airdrops[index].savedAt));
+
+        let mut changed: Vec<usize> = Vec::new();
+        for index in order {
+            let airdrop = &mut airdrops[index];
+            if let Some(&position) = self.by_tweet.get(&airdrop.tweetId) {
+                airdrop.clusterId = Some(self.clusters[position].cluster_id.clone());
+                continue;
+            }
+            let fingerprint = fingerprint(airdrop, self.min_words);
+            let position = match self.closest(&fingerprint, now - self.window) {
+                Some(position) => position,
+                None => self.open_cluster(airdrop, now),
+            };
+            self.join(position, airdrop, fingerprint, now);
+            airdrop.clusterId = Some(self.clusters[position].cluster_id.clone());
+            if !changed.contains(&position) {
+                changed.push(position);
+            }
+        }
+        changed.into_iter().map(|position| self.clusters[position].clone()).collect()
+    }
+
+    /// Returns the position of the open cluster closest to `fingerprint` that it matches.
+    fn closest(&self, fingerprint: &Fingerprint, open_since: DateTime<Utc>) -> Option<usize> {
+        self.clusters
+            .iter()
+            .enumerate()
+            .filter(|(_, cluster)| cluster.last_seen_at >= open_since)
+            .filter_map(|(position, cluster)| {
+                let distance = fingerprint.simhash.and_then(|simhash| {
+                    cluster.fingerprints.iter().map(|known| (simhash ^ *known as u64).count_ones()).min()
+                });
+                let shares_link = fingerprint.links.iter().any(|link| cluster.links.contains(link));
+                let shares_cashtag = fingerprint.cashtags.iter().any(|cashtag| cluster.cashtags.contains(cashtag));
+                let matches = match distance {
+                    Some(distance) => distance <= self.max_distance || (shares_link && distance <= 2 * self.max_distance),
+                    None => shares_link && shares_cashtag,
+                };
+                // Clusters matched without a distance rank after every measured one.
+                matches.then(|| (position, distance.unwrap_or(u32::MAX)))
+            })
+            .min_by_key(|&(_, distance)| distance)
+            .map(|(position, _)| position)
+    }
+
+    /// Opens an empty cluster named after `airdrop` and returns its position.
+    fn open_cluster(&mut self, airdrop: &Airdrop, now: DateTime<Utc>) -> usize {
+        let position = self.clusters.len();
+        self.by_id.insert(airdrop.tweetId.clone(), position);
+        self.clusters.push(TweetCluster {
+            cluster_id: airdrop.tweetId.clone(),
+            canonical_tweet_id: airdrop.tweetId.clone(),
+            canonical_created_at: airdrop.createdAt,
+            tweet_ids: Vec::new(),
+            author_handles: Vec::new(),
+            fingerprints: Vec::new(),
+            links: Vec::new(),
+            cashtags: Vec::new(),
+            first_seen_at: now,
+            last_seen_at: now,
+        });
+        position
+    }
+
+    /// Adds `airdrop` to the cluster at `position`, making it the canonical record if it is
+    /// the earliest tweet.
+    fn join(&mut self, position: usize, airdrop: &Airdrop, fingerprint: Fingerprint, now: DateTime<Utc>) {
+        self.by_tweet.insert(airdrop.tweetId.clone(), position);
+        let cluster = &mut self.clusters[position];
+        cluster.tweet_ids.push(airdrop.tweetId.clone());
+        if let Some(author) = airdrop.authorHandle.as_deref().map(|handle| handle.trim_start_matches('@').to_lowercase())
+            && !cluster.author_handles.contains(&author)
+        {
+            cluster.author_handles.push(author);
+        }
+        if let Some(simhash) = fingerprint.simhash
+            && cluster.fingerprints.len() < MAX_FINGERPRINTS
+            && !cluster.fingerprints.contains(&(simhash as i64))
+        {
+            cluster.fingerprints.push(simhash as i64);
+        }
+        for link in fingerprint.links {
+            if cluster.links.len() < MAX_LINKS && !cluster.links.contains(&link) {
+                cluster.links.push(link);
+            }
+        }
+        for cashtag in fingerprint.cashtags {
+            if !cluster.cashtags.contains(&cashtag) {
+                cluster.cashtags.push(cashtag);
+            }
+        }
+        if let Some(created_at) = airdrop.createdAt
+            && cluster.canonical_created_at.is_none_or(|canonical| created_at < canonical)
+        {
+            cluster.canonical_tweet_id = airdrop.tweetId.clone();
+            cluster.canonical_created_at = Some(created_at);
+        }
+        cluster.last_seen_at = now;
+    }
+}
+
+/// Computes the fingerprint of a record.
+fn fingerprint(airdrop: &Airdrop, min_words: usize) -> Fingerprint {
+    let quoted = airdrop.quotedTweet.as_ref().and_then(|quoted| quoted.text.as_deref());
+    let mut words: Vec<String> = airdrop.text.as_deref().into_iter().chain(quoted).flat_map(normalized_words).collect();
+    words.sort();
+    words.dedup();
+    let links = airdrop
+        .resolvedLinks
+        .iter()
+        .filter_map(|link| link.expanded.as_deref())
+        .filter_map(normalized_link)
+        .collect();
+    Fingerprint {
+        simhash: (words.len() >= min_words).then(|| simhash(&words)),
+        links,
+        cashtags: airdrop.cashtags.iter().map(|cashtag| cashtag.to_uppercase()).collect(),
+    }
+}
+
+/// Returns the lower-cased words of a text without URLs, mentions, punctuation and stop
+/// words; cashtags and hashtags keep their word.
+fn normalized_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split_whitespace()
+        .filter(|word| !word.contains("://") && !word.starts_with("www.") && !word.starts_with('@'))
+        .map(|word| word.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase())
+        .filter(|word| !word.is_empty() && !STOP_WORDS.contains(&word.as_str()))
+}
+
+/// Returns the host and path of a URL, e.g. `galxe.com/project/campaign/GC1`, or `None` for a
+/// bare domain, which too many unrelated tweets share.
+fn normalized_link(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let path = url.path().trim_matches('/');
+    (!path.is_empty()).then(|| format!("{}/{}", host, path))
+}
+
+/// Computes the SimHash of the words: every bit is set when more words have it set in their
+/// hash than not.
+fn simhash(words: &[String]) -> u64 {
+    let mut weights = [0i32; 64];
+    for word in words {
+        let hash = fnv1a(word);
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if hash >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0, |simhash, (bit, _)| simhash | 1 << bit)
+}
+
+/// The 64-bit FNV-1a hash, which unlike the standard hasher is stable across Rust versions,
+/// as stored fingerprints require.
+fn fnv1a(word: &str) -> u64 {
+    word
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
@@ -0,0 +1,419 @@
+//! This module extracts the dates an airdrop tweet announces ("snapshot on Oct 30", "ends in
+//! 48h", "claim until 12/01") as typed deadlines in UTC.
+//!
+//! Each sentence is scanned for date expressions: ISO dates, `M/D` and `M/D/YYYY` dates (read
+//! as `D/M` when the first number cannot be a month), month names with a day, `today` and
+//! `tomorrow`, optionally followed by a time ("at 14:00 UTC", "2pm"), and relative durations
+//! ("in 48h", "within 3 days", "24 hours left"). Relative expressions and dates without a year
+//! are resolved against the tweet's `createdAt`; dates without a time resolve to midnight UTC.
+//!
+//! A date only counts when a keyword in its sentence says what it is (snapshot, claim, TGE, an
+//! end or a start). A slash date without a year only counts after a preposition that
+//! introduces a date ("until 12/01", "on 10/30"), so thread numbering such as "1/7" and
+//! "24/7" is never read as a date.
+
+use crate::scraper::user_profile::Airdrop;
+use crate::utils::sentences;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What a date marks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeadlineKind {
+    /// The eligibility snapshot.
+    Snapshot,
+    /// Claiming opens.
+    ClaimStart,
+    /// Claiming closes.
+    ClaimEnd,
+    /// The token generation event, launch or listing.
+    Tge,
+    /// A campaign, quest or sale starts.
+    Start,
+    /// A campaign, quest or sale ends.
+    End,
+}
+
+impl DeadlineKind {
+    /// Returns `true` for the kinds after which the opportunity is gone.
+    fn is_cutoff(self) -> bool {
+        matches!(self, Self::Snapshot | Self::ClaimEnd | Self::End)
+    }
+}
+
+/// A date announced by a tweet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deadline {
+    /// What the date marks.
+    pub kind: DeadlineKind,
+    /// The resolved time, stored as a BSON date so records can be sorted by it.
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub at: DateTime<Utc>,
+    /// The expression as written in the tweet, e.g. `Oct 30 at 14:00 UTC`.
+    pub expression: String,
+}
+
+/// Keyword groups that say what a nearby date marks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keyword {
+    Snapshot,
+    Claim,
+    Tge,
+    End,
+    Start,
+}
+
+/// Month names and their common abbreviations, lower-cased, with the month number.
+const MONTHS: [(&str, u32); 24] = [
+    ("january", 1),
+    ("jan", 1),
+    ("february", 2),
+    ("feb", 2),
+    ("march", 3),
+    ("mar", 3),
+    ("april", 4),
+    ("apr", 4),
+    ("may", 5),
+    ("june", 6),
+    ("jun", 6),
+    ("july", 7),
+    ("jul", 7),
+    ("august", 8),
+    ("aug", 8),
+    ("september", 9),
+    ("sept", 9),
+    ("sep", 9),
+    ("october", 10),
+    ("oct", 10),
+    ("november", 11),
+    ("nov", 11),
+    ("december", 12),
+    ("dec", 12),
+];
+
+/// Words after which a slash date without a year is read as a date.
+const DATE_PREPOSITIONS: [&str; 9] = ["on", "until", "till", "by", "before", "after", "from", "through", "thru"];
+
+/// Sets `deadlines` and `nextDeadline` on every record from its text and `createdAt`.
+pub fn extract_airdrops(airdrops: &mut [Airdrop]) {
+    for airdrop in airdrops.iter_mut() {
+        airdrop.deadlines = extract_deadlines(airdrop.text.as_deref().unwrap_or_default(), airdrop.createdAt);
+        airdrop.nextDeadline = next_deadline(&airdrop.deadlines);
+    }
+}
+
+/// Returns the earliest cutoff (snapshot, claim end or end) among `deadlines`, the date by
+/// which a reader has to act.
+pub fn next_deadline(deadlines: &[Deadline]) -> Option<DateTime<Utc>> {
+    deadlines.iter().filter(|deadline| deadline.kind.is_cutoff()).map(|deadline| deadline.at).min()
+}
+
+/// Extracts the deadlines of a tweet text in order of appearance.
+///
+/// # Arguments
+/// * `text` - The tweet text.
+/// * `created_at` - When the tweet was posted. Without it, only dates with an explicit year
+///   are resolved.
+pub fn extract_deadlines(text: &str, created_at: Option<DateTime<Utc>>) -> Vec<Deadline> {
+    let mut deadlines: Vec<Deadline> = Vec::new();
+    for sentence in sentences(text) {
+        let raw: Vec<&str> = sentence.split_whitespace().collect();
+        let words: Vec<String> = raw.iter().map(|word| clean_word(word)).collect();
+        let mut index = 0;
+        while index < words.len() {
+            let Some((at, consumed)) = parse_expression(&words, index, created_at) else {
+                index += 1;
+                continue;
+            };
+            if let Some(kind) = classify(&words, index, index + consumed) {
+                let deadline = Deadline {
+                    kind,
+                    at,
+                    expression: raw[index..index + consumed].join(" ").trim_end_matches([',', '.', '!', ';']).to_string(),
+                };
+                if !deadlines.iter().any(|known| known.kind == deadline.kind && known.at == deadline.at) {
+                    deadlines.push(deadline);
+                }
+            }
+            index += consumed;
+        }
+    }
+    deadlines
+}
+
+/// Parses a date or relative expression starting at `index`.
+///
+/// # Returns
+/// `Option<(DateTime<Utc>, usize)>`: The resolved time and the number of words consumed.
+fn parse_expression(words: &[String], index: usize, created_at: Option<DateTime<Utc>>) -> Option<(DateTime<Utc>, usize)> {
+    if let Some((duration, consumed)) = parse_relative(words, index) {
+        return Some((created_at?.checked_add_signed(duration)?, consumed));
+    }
+    let (date, consumed) = parse_date(words, index, created_at)?;
+    let (time, time_consumed) = parse_time(words, index + consumed).unwrap_or((NaiveTime::MIN, 0));
+    Some((date.and_time(time).and_utc(), consumed + time_consumed))
+}
+
+/// Parses "in 48h", "within 3 days", "next 24 hours" or "48h left".
+fn parse_relative(words: &[String], index: usize) -> Option<(Duration, usize)> {
+    let word = words[index].as_str();
+    if matches!(word, "in" | "within" | "next") {
+        let (duration, consumed) = parse_duration(words, index + 1)?;
+        return Some((duration, consumed + 1));
+    }
+    let (duration, consumed) = parse_duration(words, index)?;
+    let follower = words.get(index + consumed).map(String::as_str);
+    matches!(follower, Some("left" | "remaining")).then_some((duration, consumed + 1))
+}
+
+/// Parses a duration written as one word ("48h", "3days") or two ("48 hours").
+fn parse_duration(words: &[String], index: usize) -> Option<(Duration, usize)> {
+    let word = words.get(index)?;
+    let digits: String = word.chars().take_while(char::is_ascii_digit).collect();
+    // Durations beyond a few years are not deadlines, and would overflow `Duration`.
+    let amount: i64 = digits.parse().ok().filter(|amount| *amount <= 10_000)?;
+    let (unit, consumed) = match &word[digits.len()..] {
+        "" => (words.get(index + 1)?.as_str(), 2),
+        unit => (unit, 1),
+    };
+    let duration = match unit {
+        "h" | "hr" | "hrs" | "hour" | "hours" => Duration::hours(amount),
+        "d" | "day" | "days" => Duration::days(amount),
+        "w" | "wk" | "wks" | "week" | "weeks" => Duration::weeks(amount),
+        "min" | "mins" | "minute" | "minutes" => Duration::minutes(amount),
+        _ => return None,
+    };
+    Some((duration, consumed))
+}
+
+/// Parses a calendar date starting at `index`.
+fn parse_date(words: &[String], index: usize, created_at: Option<DateTime<Utc>>) -> Option<(NaiveDate, usize)> {
+    let word = words[index].as_str();
+    let today = created_at.map(|created_at| created_at.date_naive());
+    match word {
+        "today" | "tonight" => return Some((today?, 1)),
+        "tomorrow" => return Some((today?.succ_opt()?, 1)),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        return Some((date, 1));
+    }
+    if word.contains('/') {
+        let parts: Vec<u32> = word.split('/').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+        let introduced = index.checked_sub(1).is_some_and(|previous| DATE_PREPOSITIONS.contains(&words[previous].as_str()));
+        let (first, second, year) = match parts[..] {
+            [first, second] if introduced => (first, second, None),
+            [first, second, year] if year >= 2000 => (first, second, Some(year as i32)),
+            [first, second, year] if year < 100 => (first, second, Some(2000 + year as i32)),
+            _ => return None,
+        };
+        // Month first, as X renders dates, unless the first number cannot be a month.
+        let (month, day) = if first > 12 { (second, first) } else { (first, second) };
+        return Some((resolve_date(year, month, day, created_at)?, 1));
+    }
+
+    // "Oct 30", "October 30th, 2026", "30 Oct", "30th of October".
+    let (month, day, consumed) = if let Some(month) = month_of(word) {
+        (month, day_of(words.get(index + 1)?)?, 2)
+    } else {
+        let day = day_of(word)?;
+        match words.get(index + 1).map(String::as_str) {
+            Some("of") => (month_of(words.get(index + 2)?)?, day, 3),
+            Some(next) => (month_of(next)?, day, 2),
+            None => return None,
+        }
+    };
+    let year = words
+        .get(index + consumed)
+        .filter(|year| year.len() == 4)
+        .and_then(|year| year.parse::<i32>().ok())
+        .filter(|year| (2000..2100).contains(year));
+    let date = resolve_date(year, month, day, created_at)?;
+    Some((date, consumed + usize::from(year.is_some())))
+}
+
+/// Builds a date, inferring a missing year from `created_at`: the year of the tweet, or the
+/// next one when the date would lie more than two months before the tweet ("Jan 5" posted in
+/// December).
+fn resolve_date(year: Option<i32>, month: u32, day: u32, created_at: Option<DateTime<Utc>>) -> Option<NaiveDate> {
+    if let Some(year) = year {
+        return NaiveDate::from_ymd_opt(year, month, day);
+    }
+    let posted = created_at?.date_naive();
+    let date = NaiveDate::from_ymd_opt(posted.year(), month, day)?;
+    if date < posted - Duration::days(60) {
+        NaiveDate::from_ymd_opt(posted.year() + 1, month, day)
+    } else {
+        Some(date)
+    }
+}
+
+/// Parses an optional time after a date: "at 14:00 UTC", "14:00", "2pm", "2:30 pm", "@ 3 PM".
+fn parse_time(words: &[String], index: usize) -> Option<(NaiveTime, usize)> {
+    let mut consumed = 0;
+    if matches!(words.get(index).map(String::as_str), Some("at" | "@")) {
+        consumed += 1;
+    }
+    let word = words.get(index + consumed)?.as_str();
+    let word = word.strip_suffix("utc").or_else(|| word.strip_suffix("gmt")).unwrap_or(word);
+    let (clock, mut meridiem) = match word.strip_suffix("am").or_else(|| word.strip_suffix("pm")) {
+        Some(clock) => (clock, Some(&word[clock.len()..])),
+        None => (word, None),
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    consumed += 1;
+    if meridiem.is_none() && let Some(next @ ("am" | "pm")) = words.get(index + consumed).map(String::as_str) {
+        meridiem = Some(next);
+        consumed += 1;
+    }
+    // A bare number is only a time with "am"/"pm" or a colon, otherwise it may be anything.
+    if meridiem.is_none() && !clock.contains(':') {
+        return None;
+    }
+    let hour = match meridiem {
+        Some("am") if hour == 12 => 0,
+        Some("pm") if hour < 12 => hour + 12,
+        _ => hour,
+    };
+    if matches!(words.get(index + consumed).map(String::as_str), Some("utc" | "gmt")) {
+        consumed += 1;
+    }
+    Some((NaiveTime::from_hms_opt(hour, minute, 0)?, consumed))
+}
+
+/// Decides what the date at `start..end` marks from the keywords of its sentence: the
+/// nearest keyword before it, else the first one after it. An end or start that follows a
+/// snapshot, claim or TGE keyword belongs to that event ("claim ends in 48h").
+fn classify(words: &[String], start: usize, end: usize) -> Option<DeadlineKind> {
+    let before = words[..start].iter().enumerate().rev().find_map(|(index, word)| keyword(word).map(|keyword| (index, keyword)));
+    let (position, nearest) = match before {
+        Some(found) => found,
+        None => words[end..].iter().enumerate().find_map(|(index, word)| keyword(word).map(|keyword| (end + index, keyword)))?,
+    };
+    let earlier = |wanted: Keyword| words[..position].iter().any(|word| keyword(word) == Some(wanted));
+
+    Some(match nearest {
+        Keyword::Snapshot => DeadlineKind::Snapshot,
+        Keyword::Tge => DeadlineKind::Tge,
+        Keyword::Claim => {
+            let between = if position < start { &words[position..start] } else { &words[end..position] };
+            // "claim until 12/01", or a countdown such as "24 hours left to claim".
+            let closes = between.iter().chain(&words[start..end]).any(|word| keyword(word) == Some(Keyword::End));
+            if closes { DeadlineKind::ClaimEnd } else { DeadlineKind::ClaimStart }
+        }
+        Keyword::End | Keyword::Start if earlier(Keyword::Snapshot) => DeadlineKind::Snapshot,
+        Keyword::End if earlier(Keyword::Claim) => DeadlineKind::ClaimEnd,
+        Keyword::Start if earlier(Keyword::Claim) => DeadlineKind::ClaimStart,
+        Keyword::End | Keyword::Start if earlier(Keyword::Tge) => DeadlineKind::Tge,
+        Keyword::End => DeadlineKind::End,
+        Keyword::Start => DeadlineKind::Start,
+    })
+}
+
+/// Returns the keyword group of a cleaned word.
+fn keyword(word: &str) -> Option<Keyword> {
+    match word {
+        "snapshot" | "snapshots" => Some(Keyword::Snapshot),
+        "claim" | "claims" | "claiming" | "claimable" => Some(Keyword::Claim),
+        "tge" | "launch" | "launches" | "launching" | "listing" | "lists" | "listed" => Some(Keyword::Tge),
+        "ends" | "end" | "ending" | "until" | "till" | "by" | "before" | "deadline" | "closes" | "close" | "closing"
+        | "expires" | "left" | "remaining" | "last" => Some(Keyword::End),
+        "starts" | "start" | "starting" | "begins" | "begin" | "opens" | "open" | "opening" | "live" | "from" => {
+            Some(Keyword::Start)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the month number of a cleaned month name or abbreviation ("oct", "october").
+fn month_of(word: &str) -> Option<u32> {
+    MONTHS.iter().find(|(name, _)| *name == word).map(|&(_, month)| month)
+}
+
+/// Returns the day of a word such as "30", "30th" or "1st".
+fn day_of(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    if !matches!(suffix, "" | "st" | "nd" | "rd" | "th") {
+        return None;
+    }
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+/// Lower-cases a word and trims the punctuation around it. The `/`, `:` and `-` that dates and
+/// times are written with stay inside the word, and a lone `@` ("@ 3pm") is kept.
+fn clean_word(word: &str) -> String {
+    word.trim_matches(|c: char| !(c.is_alphanumeric() || c == '@')).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn words(text: &str) -> Vec<String> {
+        text.split_whitespace().map(clean_word).collect()
+    }
+
+    fn posted() -> Option<DateTime<Utc>> {
+        Some(Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn parses_dates() {
+        let cases = [
+            ("1/7", 0, None),
+            ("thread 1/7", 1, None),
+            ("open 24/7", 1, None),
+            ("until 12/01", 1, Some((NaiveDate::from_ymd_opt(2026, 12, 1).unwrap(), 1))),
+            ("on 30/10", 1, Some((NaiveDate::from_ymd_opt(2026, 10, 30).unwrap(), 1))),
+            ("12/01/2027", 0, Some((NaiveDate::from_ymd_opt(2027, 12, 1).unwrap(), 1))),
+            ("oct 30", 0, Some((NaiveDate::from_ymd_opt(2026, 10, 30).unwrap(), 2))),
+            ("jan 5", 0, Some((NaiveDate::from_ymd_opt(2027, 1, 5).unwrap(), 2))),
+            ("30th of october 2026", 0, Some((NaiveDate::from_ymd_opt(2026, 10, 30).unwrap(), 4))),
+        ];
+        for (text, index, expected) in cases {
+            assert_eq!(parse_date(&words(text), index, posted()), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn parses_times() {
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        let cases = [
+            ("2pm", Some((time(14, 0), 1))),
+            ("2 pm", Some((time(14, 0), 2))),
+            ("12am", Some((time(0, 0), 1))),
+            ("at 14:00 utc", Some((time(14, 0), 3))),
+            ("@ 3:30pm", Some((time(15, 30), 2))),
+            ("14:00utc", Some((time(14, 0), 1))),
+            ("2", None),
+            ("25:00", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_time(&words(text), 0), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn extracts_deadlines() {
+        let at = |month, day, hour| Utc.with_ymd_and_hms(2026, month, day, hour, 0, 0).unwrap();
+        let cases = [
+            ("1/7 🧵 Snapshot on Oct 30", vec![(DeadlineKind::Snapshot, at(10, 30, 0))]),
+            ("We are live 24/7. Claim ends Nov 5 at 2pm UTC", vec![(DeadlineKind::ClaimEnd, at(11, 5, 14))]),
+            ("Claim until 12/01", vec![(DeadlineKind::ClaimEnd, at(12, 1, 0))]),
+            ("Claim window ends in 48h", vec![(DeadlineKind::ClaimEnd, at(10, 20, 12))]),
+            ("Follow us 24/7 for alpha", Vec::new()),
+        ];
+        for (text, expected) in cases {
+            let found: Vec<_> =
+                extract_deadlines(text, posted()).into_iter().map(|deadline| (deadline.kind, deadline.at)).collect();
+            assert_eq!(found, expected, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! This module extracts a tweet's engagement metrics (replies, reposts, likes, bookmarks and
+//! views) from its rendered HTML.
+//!
+//! Each action button of the tweet `article` (`data-testid='reply'`, `retweet`, `like`,
+//! `bookmark`) and the analytics link carry their count in an `aria-label` such as
+//! "1,234 Likes. Like". The visible text is the fallback and is usually abbreviated ("1.2K").
+//! Counts are parsed with `parse_count`, which understands the grouping separators and
+//! abbreviations of the locales X is commonly used in.
+
+use crate::errors::ScrapeResult;
+use crate::utils::parse_selector;
+use scraper::{ElementRef, Html};
+use serde::{Deserialize, Serialize};
+
+/// Engagement counts of a tweet at the time it was scraped. `None` means the count was not
+/// shown, `Some(0)` that it was shown without a number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Engagement {
+    /// Number of likes.
+    pub likes: Option<u64>,
+    /// Number of reposts (retweets).
+    pub reposts: Option<u64>,
+    /// Number of replies.
+    pub replies: Option<u64>,
+    /// Number of views.
+    pub views: Option<u64>,
+    /// Number of bookmarks.
+    pub bookmarks: Option<u64>,
+}
+
+impl Engagement {
+    /// Reads the engagement counts from a parsed tweet `article`.
+    ///
+    /// Only the tweet's own action bar is read: quoted tweets have none.
+    ///
+    /// # Returns
+    /// `ScrapeResult<Option<Engagement>>`: `None` if the article shows no engagement at all.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+    pub fn from_document(document: &Html) -> ScrapeResult<Option<Self>> {
+        let engagement = Self {
+            likes: count_of(document, "[data-testid='like'], [data-testid='unlike']")?,
+            reposts: count_of(document, "[data-testid='retweet'], [data-testid='unretweet']")?,
+            replies: count_of(document, "[data-testid='reply']")?,
+            views: count_of(document, "a[href$='/analytics']")?,
+            bookmarks: count_of(document, "[data-testid='bookmark'], [data-testid='removeBookmark']")?,
+        };
+        Ok((engagement != Self::default()).then_some(engagement))
+    }
+}
+
+/// Reads the count of the first element matching `selector`: from its `aria-label`, or from
+/// its visible text if the label has no number.
+///
+/// # Returns
+/// `ScrapeResult<Option<u64>>`: `None` if no element matches, `Some(0)` if it shows no number.
+fn count_of(document: &Html, selector: &str) -> ScrapeResult<Option<u64>> {
+    let selector = parse_selector(selector)?;
+    Ok(document.select(&selector).next().map(|element: ElementRef| {
+        element
+            .value()
+            .attr("aria-label")
+            .and_then(parse_count)
+            .or_else(|| parse_count(&element.text().collect::<String>()))
+            .unwrap_or(0)
+    }))
+}
+
+/// Multipliers of the count abbreviations X uses, by lower-cased abbreviation without a
+/// trailing dot. Spanish and Portuguese "mil" is a thousand; "M" is a million everywhere.
+const ABBREVIATIONS: [(&str, u64); 18] = [
+    ("k", 1_000),
+    ("tsd", 1_000),
+    ("mil", 1_000),
+    ("тыс", 1_000),
+    ("천", 1_000),
+    ("m", 1_000_000),
+    ("mio", 1_000_000),
+    ("mn", 1_000_000),
+    ("mln", 1_000_000),
+    ("млн", 1_000_000),
+    ("b", 1_000_000_000),
+    ("bn", 1_000_000_000),
+    ("md", 1_000_000_000),
+    ("mrd", 1_000_000_000),
+    ("млрд", 1_000_000_000),
+    ("万", 10_000),
+    ("만", 10_000),
+    ("億", 100_000_000),
+];
+
+/// Parses the first count found in a label or text, e.g. `"1,234 Likes. Like"` → 1234,
+/// `"1.2K"` → 1200, `"3 Mio."` → 3000000, `"1.234"` → 1234, `"1,2 mil"` → 1200.
+///
+/// Without an abbreviation, `.`, `,`, `'` and (narrow) spaces followed by exactly three
+/// digits are grouping separators. With one, the last `.` or `,` is the decimal separator.
+///
+/// # Returns
+/// `Option<u64>`: `None` if the input contains no number, or one too large for a `u64`.
+pub fn parse_count(input: &str) -> Option<u64> {
+    let start = input.find(|c: char| c.is_ascii_digit())?;
+    let rest = &input[start..];
+
+    // The number: digits and the separators between them.
+    let mut end = 0;
+    let chars: Vec<(usize, char)> = rest.char_indices().collect();
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        let is_separator = matches!(c, '.' | ',' | '\'' | ' ' | '\u{a0}' | '\u{202f}')
+            && chars.get(i + 1).is_some_and(|&(_, next)| next.is_ascii_digit());
+        if c.is_ascii_digit() || is_separator {
+            end = offset + c.len_utf8();
+        } else {
+            break;
+        }
+    }
+    let number = &rest[..end];
+
+    // The abbreviation: the word right after the number, if it is a known one.
+    let word: String = rest[end..]
+        .trim_start_matches([' ', '\u{a0}', '\u{202f}'])
+        .chars()
+        .take_while(|c| c.is_alphabetic())
+        .collect::<String>()
+        .to_lowercase();
+    // CJK abbreviations are written without a space before the counted noun ("1.2万件").
+    let multiplier = ABBREVIATIONS
+        .iter()
+        .find(|(abbreviation, _)| {
+            *abbreviation == word || (!abbreviation.is_ascii() && abbreviation.chars().count() == 1 && word.starts_with(abbreviation))
+        })
+        .map(|&(_, multiplier)| multiplier);
+
+    match multiplier {
+        Some(multiplier) => {
+            let (whole, fraction) = match number.rfind(['.', ',']) {
+                Some(index) if number.len() - index - 1 < 3 => (&number[..index], &number[index + 1..]),
+                _ => (number, ""),
+            };
+            let whole = digits_only(whole).parse::<u64>().ok()?;
+            let fraction_value = fraction.parse::<u64>().unwrap_or(0);
+            let fraction_scale = 10u64.pow(fraction.len() as u32);
+            // A huge abbreviated number would overflow; it is no count anyone can have.
+            whole.checked_mul(multiplier)?.checked_add(fraction_value.checked_mul(multiplier)? / fraction_scale)
+        }
+        None => {
+            // A separator followed by fewer than three digits can only be a decimal one;
+            // counts are whole numbers, so the fraction is dropped.
+            let whole = match number.rfind(['.', ',']) {
+                Some(index) if number.len() - index - 1 != 3 => &number[..index],
+                _ => number,
+            };
+            digits_only(whole).parse().ok()
+        }
+    }
+}
+
+/// Removes every grouping separator from a number.
+fn digits_only(number: &str) -> String {
+    number.chars().filter(char::is_ascii_digit).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_counts() {
+        let cases = [
+            ("1.2K", Some(1_200)),
+            ("3M", Some(3_000_000)),
+            ("1,234", Some(1_234)),
+            ("1.234", Some(1_234)),
+            ("1 234", Some(1_234)),
+            ("1\u{202f}234", Some(1_234)),
+            ("1,2 K", Some(1_200)),
+            ("1.2万", Some(12_000)),
+            ("3.5億", Some(350_000_000)),
+            ("1,234 Likes. Like", Some(1_234)),
+            ("3 Mio.", Some(3_000_000)),
+            ("1,2 mil", Some(1_200)),
+            ("12.5", Some(12)),
+            ("Like", None),
+            ("99999999999999999999", None),
+            ("99999999999999999B", None),
+            ("18446744073709551615K", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_count(input), expected, "{}", input);
+        }
+    }
+}
@@ -0,0 +1,393 @@
+//! This module processes the GraphQL timeline responses captured in a `ScrapedBatch`
+//! (`UserTweets`, `TweetDetail`, `SearchTimeline`) into structured `Airdrop` data, with exact
+//! tweet ids, timestamps, engagement counts and expanded URLs.
+//!
+//! X renames and nests these payloads often, so the JSON is read as an untyped
+//! `serde_json::Value` and tweets are located by shape: every `instructions` array is walked
+//! for timeline entries, and each entry's `tweet_results.result` is read. Anything that does
+//! not look like a tweet is skipped instead of failing the batch.
+
+use crate::errors::{ScrapeError, ScrapeResult};
+use crate::processing::classification::{TweetKind, TweetRelations};
+use crate::processing::engagement::Engagement;
+use crate::processing::html_processor::{TweetHtmlProcessor, TweetText};
+use crate::processing::links::{ResolvedLink, urls};
+use crate::processing::media::{LinkCard, Media, MediaKind};
+use crate::processing::quoted_tweet::QuotedTweet;
+use crate::processing::tags::TweetTags;
+use crate::processing::thread::link_threads;
+use crate::processing::traits::HtmlSetProcessor;
+use crate::scraper::batch::ScrapedBatch;
+use crate::scraper::user_profile::Airdrop;
+use crate::utils::handle_from_url;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// The timestamp format of `legacy.created_at`, e.g. `Wed Oct 10 20:19:24 +0000 2018`.
+const CREATED_AT_FORMAT: &str = "%a %b %d %H:%M:%S %z %Y";
+
+/// The default `HtmlSetProcessor`: processes the captured GraphQL responses with
+/// `process_graphql_to_airdrops`, then falls back to the `TweetHtmlProcessor` for every
+/// tweet none of the responses covers (e.g. when capture was disabled or a response was lost).
+pub struct GraphqlProcessor;
+
+impl HtmlSetProcessor for GraphqlProcessor {
+    fn process_batch(&self, batch: &ScrapedBatch) -> ScrapeResult<Vec<Airdrop>> {
+        let mut airdrops = process_graphql_to_airdrops(batch)?;
+        // The HTML records below are linked to their threads by the `TweetHtmlProcessor`.
+        link_threads(batch, &mut airdrops);
+        let from_graphql = airdrops.len();
+        let covered: HashSet<String> = airdrops.iter().map(|airdrop| airdrop.tweetId.clone()).collect();
+        airdrops.extend(
+            TweetHtmlProcessor
+                .process_batch(batch)?
+                .into_iter()
+                .filter(|airdrop| !covered.contains(&airdrop.tweetId)),
+        );
+        println!(
+            "Processed {} tweets from GraphQL responses and {} from HTML.",
+            from_graphql,
+            airdrops.len() - from_graphql
+        );
+        Ok(airdrops)
+    }
+}
+
+/// Processes the GraphQL responses of a `ScrapedBatch` into a `Vec` of `Airdrop` structs.
+///
+/// Tweets are returned in the order of the responses and of the timeline entries within
+/// them, each tweet once. Tweets authored by another account than the batch's profile (for
+/// example replies from other users in a `TweetDetail` conversation) are skipped, but the
+/// profile's reposts are kept. Every record is attributed to the batch's `author_id`, except
+/// reposts, which are attributed to their author's handle, like the HTML path does.
+///
+/// Only the tweets the scraper collected as HTML are kept (see `ScrapedBatch::tweet_ids`). A
+/// captured page holds about 20 tweets past the point where scrolling stopped, and those lie
+/// beyond the tweet limit, the previous run's watermark or the backfill's `until` date.
+///
+/// # Arguments
+/// * `batch` - A reference to the `ScrapedBatch` produced by the scraper.
+///
+/// # Returns
+/// `ScrapeResult<Vec<Airdrop>>`: The records found in the responses, possibly empty.
+///
+/// # Errors
+/// * Returns `ScrapeError::Parse` if the batch has an empty `author_id`.
+/// * Returns `ScrapeError::InvalidSelector` if the ids of the collected HTML cannot be read.
+/// * Logs a warning and skips a response whose body is not valid JSON.
+pub fn process_graphql_to_airdrops(batch: &ScrapedBatch) -> ScrapeResult<Vec<Airdrop>> {
+    if batch.author_id.trim().is_empty() {
+        return Err(ScrapeError::Parse(format!("batch for {} has no author_id", batch.profile_url)));
+    }
+    let handle = handle_from_url(&batch.profile_url);
+    let collected = batch.tweet_ids()?;
+
+    let mut airdrops = Vec::new();
+    let mut seen_ids = HashSet::new();
+    for response in &batch.graphql {
+        let json: Value = match serde_json::from_str(&response.body) {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("Warning: Skipping {} response that is not valid JSON: {}", response.operation, e);
+                continue;
+            }
+        };
+        for item in timeline_tweets(&json) {
+            if let Some(screen_name) = author_screen_name(item.tweet)
+                && !screen_name.eq_ignore_ascii_case(handle)
+            {
+                continue;
+            }
+            if let Some(airdrop) = tweet_to_airdrop(&item, &batch.author_id)
+                && collected.contains(&airdrop.tweetId)
+                && seen_ids.insert(airdrop.tweetId.clone())
+            {
+                airdrops.push(airdrop);
+            }
+        }
+    }
+    Ok(airdrops)
+}
+
+/// A tweet of a timeline entry.
+struct TimelineTweet<'a> {
+    /// The unwrapped tweet object.
+    tweet: &'a Value,
+    /// Whether the entry is the profile's pinned tweet.
+    pinned: bool,
+}
+
+/// Returns the tweets of every timeline entry in the response, in timeline order.
+///
+/// Handles single-tweet entries (`content.itemContent`) as well as conversation modules
+/// (`content.items[].item.itemContent`), and both `entries` arrays and the single `entry` of
+/// a pin instruction (`TimelinePinEntry`), whose tweet is marked as pinned.
+fn timeline_tweets(json: &Value) -> Vec<TimelineTweet<'_>> {
+    let mut instruction_lists = Vec::new();
+    find_key(json, "instructions", &mut instruction_lists);
+
+    let mut tweets = Vec::new();
+    for instruction in instruction_lists.into_iter().filter_map(Value::as_array).flatten() {
+        let entries: Vec<&Value> = match instruction.get("entries").and_then(Value::as_array) {
+            Some(entries) => entries.iter().collect(),
+            None => instruction.get("entry").into_iter().collect(),
+        };
+        let pin_instruction = instruction["type"].as_str() == Some("TimelinePinEntry");
+        for entry in entries {
+            let content = &entry["content"];
+            let module_items = content["items"].as_array().into_iter().flatten();
+            let item_contents = std::iter::once(&content["itemContent"])
+                .chain(module_items.map(|item| &item["item"]["itemContent"]));
+            tweets.extend(item_contents.filter_map(|item_content| {
+                let tweet = unwrap_tweet(&item_content["tweet_results"]["result"])?;
+                let pinned = pin_instruction || item_content["socialContext"]["contextType"].as_str() == Some("Pin");
+                Some(TimelineTweet { tweet, pinned })
+            }));
+        }
+    }
+    tweets
+}
+
+/// Collects the values of every `key` found anywhere in `value`.
+fn find_key<'a>(value: &'a Value, key: &str, found: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map {
+                if name == key {
+                    found.push(child);
+                } else {
+                    find_key(child, key, found);
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| find_key(item, key, found)),
+        _ => {}
+    }
+}
+
+/// Unwraps a `tweet_results.result`, which is either a `Tweet` or a
+/// `TweetWithVisibilityResults` wrapping one.
+///
+/// # Returns
+/// `Option<&Value>`: The tweet, or `None` if the value has no `rest_id` or `legacy` object
+/// (e.g. a `TweetTombstone` for a deleted tweet).
+fn unwrap_tweet(result: &Value) -> Option<&Value> {
+    let tweet = match result["__typename"].as_str() {
+        Some("TweetWithVisibilityResults") => &result["tweet"],
+        _ => result,
+    };
+    (tweet["rest_id"].is_string() && tweet["legacy"].is_object()).then_some(tweet)
+}
+
+/// Returns the screen name of the tweet's author, from either the legacy or the current
+/// user schema.
+fn author_screen_name(tweet: &Value) -> Option<&str> {
+    let user = &tweet["core"]["user_results"]["result"];
+    user["legacy"]["screen_name"]
+        .as_str()
+        .or_else(|| user["core"]["screen_name"].as_str())
+}
+
+/// Builds an `Airdrop` from a timeline tweet.
+///
+/// A repost is read from the original tweet it wraps (`legacy.retweeted_status_result`), so
+/// the record carries the original id, text and author. A quoted tweet
+/// (`quoted_status_result`) is read into the nested `quotedTweet` record.
+fn tweet_to_airdrop(item: &TimelineTweet, author_id: &str) -> Option<Airdrop> {
+    let reposted = unwrap_tweet(&item.tweet["legacy"]["retweeted_status_result"]["result"]);
+    let tweet = reposted.unwrap_or(item.tweet);
+    let tweet_id = tweet["rest_id"].as_str()?.to_string();
+    let legacy = &tweet["legacy"];
+    let relations = tweet_relations(item, tweet, reposted.is_some());
+    let text = tweet_text(tweet);
+    let quoted_tweet = unwrap_tweet(&tweet["quoted_status_result"]["result"]).map(|quoted| {
+        let quoted_text = tweet_text(quoted);
+        QuotedTweet {
+            tweet_id: quoted["rest_id"].as_str().map(String::from),
+            parent_tweet_id: tweet_id.clone(),
+            author_handle: author_screen_name(quoted).map(String::from),
+            text: quoted_text.text,
+            created_at: created_at(quoted),
+            links: urls(&quoted_text.links),
+            mentioned_users: quoted_text.tags.mentions,
+        }
+    });
+
+    let plain_text = text.text.as_deref().unwrap_or_default();
+    let keywords = text.tags.keywords(plain_text);
+    let token_name = text.tags.token_candidate(plain_text);
+
+    let tweet_author_id = match relations.kind {
+        TweetKind::Repost => relations.author_handle.clone(),
+        _ => Some(author_id.to_string()),
+    };
+
+    Some(Airdrop {
+        tweetId: tweet_id,
+        text: text.text,
+        authorId: tweet_author_id,
+        createdAt: created_at(tweet),
+        savedAt: Utc::now(), // Timestamp when the Airdrop was processed.
+        deepness: 2,
+        keywords,
+        tokenName: token_name,
+        links: urls(&text.links),
+        mentionedUsers: text.tags.mentions,
+        cashtags: text.tags.cashtags,
+        hashtags: text.tags.hashtags,
+        resolvedLinks: text.links,
+        engagement: Some(Engagement {
+            likes: legacy["favorite_count"].as_u64(),
+            reposts: legacy["retweet_count"].as_u64(),
+            replies: legacy["reply_count"].as_u64(),
+            // View counts are sent as strings, and only once X has counted them.
+            views: tweet["views"]["count"].as_str().and_then(|views| views.parse().ok()),
+            bookmarks: legacy["bookmark_count"].as_u64(),
+        }),
+        tweetType: Some(relations.kind),
+        authorHandle: relations.author_handle,
+        repostedBy: relations.reposted_by,
+        inReplyToTweetId: relations.in_reply_to_tweet_id,
+        inReplyToHandle: relations.in_reply_to_handle,
+        quotedTweetId: relations.quoted_tweet_id,
+        quotedTweet: quoted_tweet,
+        threadRootId: None,
+        threadPosition: None,
+        media: tweet_media(tweet),
+        chainAddresses: Vec::new(), // Detected once the links are resolved.
+        relevance: None, // Scored once the addresses are detected.
+        tasks: Vec::new(), // Extracted once the links are resolved.
+        deadlines: Vec::new(), // Extracted with the tasks.
+        nextDeadline: None,
+        rewards: Vec::new(), // Extracted with the tasks.
+        chains: Vec::new(), // Classified once the addresses are detected.
+        riskFlags: Vec::new(), // Checked once the links are resolved.
+        clusterId: None, // Assigned once the records are scored.
+    })
+}
+
+/// Reads the attached media (`legacy.extended_entities.media`) and the link preview card
+/// (`card.legacy.binding_values`) of a tweet object.
+///
+/// # Returns
+/// `Option<Media>`: `None` if the tweet has neither media nor a card.
+fn tweet_media(tweet: &Value) -> Option<Media> {
+    let mut media = Media::default();
+    for item in tweet["legacy"]["extended_entities"]["media"].as_array().into_iter().flatten() {
+        let kind = match item["type"].as_str() {
+            Some("video") => MediaKind::Video,
+            Some("animated_gif") => MediaKind::Gif,
+            _ => MediaKind::Photo,
+        };
+        if let Some(url) = item["media_url_https"].as_str() {
+            let alt_text = item["ext_alt_text"].as_str().filter(|alt| !alt.is_empty()).map(String::from);
+            media.push(kind, url, alt_text);
+        }
+    }
+
+    let bindings = tweet["card"]["legacy"]["binding_values"].as_array();
+    if let Some(bindings) = bindings {
+        let value_of = |key: &str| {
+            bindings
+                .iter()
+                .find(|binding| binding["key"].as_str() == Some(key))
+                .and_then(|binding| {
+                    let value = &binding["value"];
+                    value["string_value"].as_str().or_else(|| value["image_value"]["url"].as_str())
+                })
+                .map(String::from)
+        };
+        let card = LinkCard {
+            url: value_of("card_url").or_else(|| tweet["card"]["legacy"]["url"].as_str().map(String::from)),
+            title: value_of("title"),
+            description: value_of("description"),
+            domain: value_of("domain").or_else(|| value_of("vanity_url")),
+            image_url: value_of("thumbnail_image_large").or_else(|| value_of("thumbnail_image_original")),
+        };
+        media.card = (card != LinkCard::default()).then_some(card);
+    }
+
+    (media != Media::default()).then_some(media)
+}
+
+/// Reads the text of a tweet object with its tags and links.
+///
+/// Long tweets (`note_tweet`) are read from their full text. Shortened `t.co` links in the
+/// text are replaced by their expanded URL, and the trailing links to attached media are
+/// removed, so the text reads like the rendered tweet.
+fn tweet_text(tweet: &Value) -> TweetText {
+    let legacy = &tweet["legacy"];
+    let note = &tweet["note_tweet"]["note_tweet_results"]["result"];
+    let (raw_text, entities) = match note["text"].as_str() {
+        Some(text) => (text, &note["entity_set"]),
+        None => (legacy["full_text"].as_str().unwrap_or_default(), &legacy["entities"]),
+    };
+
+    let mut text = raw_text.to_string();
+    let mut links: Vec<ResolvedLink> = Vec::new();
+    for url in entities["urls"].as_array().into_iter().flatten() {
+        let (Some(short), Some(expanded)) = (url["url"].as_str(), url["expanded_url"].as_str()) else {
+            continue;
+        };
+        text = text.replace(short, expanded);
+        if (expanded.starts_with("http://") || expanded.starts_with("https://"))
+            && !links.iter().any(|link| link.short == short)
+        {
+            links.push(ResolvedLink::new(short, Some(expanded.to_string())));
+        }
+    }
+    for media in legacy["entities"]["media"].as_array().into_iter().flatten() {
+        if let Some(short) = media["url"].as_str() {
+            text = text.replace(short, "");
+        }
+    }
+    let text = unescape_html(text.trim());
+
+    let entity_texts = |kind: &str, key: &'static str| {
+        entities[kind].as_array().into_iter().flatten().filter_map(move |entity| entity[key].as_str())
+    };
+    let tags = TweetTags::from_parts(
+        entity_texts("symbols", "text"),
+        entity_texts("hashtags", "text"),
+        entity_texts("user_mentions", "screen_name"),
+        &text,
+    );
+
+    TweetText {
+        text: (!text.is_empty()).then_some(text),
+        tags,
+        links,
+    }
+}
+
+/// Parses the `legacy.created_at` of a tweet object.
+fn created_at(tweet: &Value) -> Option<DateTime<Utc>> {
+    tweet["legacy"]["created_at"]
+        .as_str()
+        .and_then(|created_at| DateTime::parse_from_str(created_at, CREATED_AT_FORMAT).ok())
+        .map(|created_at| created_at.with_timezone(&Utc))
+}
+
+/// Classifies a timeline tweet from the reply and quote fields of `tweet`, the record's
+/// tweet. For a repost, that is the original tweet and the reposter is the author of the
+/// timeline tweet wrapping it.
+fn tweet_relations(item: &TimelineTweet, tweet: &Value, is_repost: bool) -> TweetRelations {
+    let legacy = &tweet["legacy"];
+    let string_of = |value: &Value| value.as_str().map(String::from);
+
+    let relations = TweetRelations {
+        kind: TweetKind::Original,
+        author_handle: author_screen_name(tweet).map(String::from),
+        reposted_by: is_repost.then(|| author_screen_name(item.tweet).unwrap_or_default().to_string()),
+        in_reply_to_tweet_id: string_of(&legacy["in_reply_to_status_id_str"]),
+        in_reply_to_handle: string_of(&legacy["in_reply_to_screen_name"]),
+        quoted_tweet_id: string_of(&legacy["quoted_status_id_str"]),
+    };
+    relations.classified(item.pinned, legacy["is_quote_status"].as_bool().unwrap_or(false))
+}
+
+/// Reverts the HTML escaping X applies to `full_text` (`&amp;`, `&lt;`, `&gt;`).
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
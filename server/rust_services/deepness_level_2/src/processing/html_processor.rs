@@ -1,28 +1,23 @@
-//! This module is responsible for processing raw HTML content, specifically collected tweet HTML,
-//! and transforming it into structured `Airdrop` data. It extracts relevant information
-//! such as tweet ID, text, author, creation date, mentioned users, and links.
-
+//! This module is responsible for processing raw HTML content, specifically the tweet HTML
+//! collected in a `ScrapedBatch`, and transforming it into structured `Airdrop` data. It
+//! extracts relevant information such as tweet ID, text, author, creation date, mentioned
+//! users, links, media and engagement counts.
+
+use crate::errors::{ScrapeError, ScrapeResult};
+use crate::processing::classification::{TweetKind, TweetRelations};
+use crate::processing::engagement::Engagement;
+use crate::processing::links::{ResolvedLink, expanded_from_anchor, urls};
+use crate::processing::media::Media;
+use crate::processing::quoted_tweet::{QuotedTweet, is_inside, quote_card};
+use crate::processing::tags::TweetTags;
+use crate::processing::thread::link_threads;
+use crate::processing::traits::HtmlSetProcessor;
+use crate::scraper::batch::ScrapedBatch;
+use crate::scraper::timeline::TimelineEntry;
 use crate::scraper::user_profile::Airdrop;
-use anyhow::{anyhow, Result};
-use chrono::{DateTime, Utc};
-use scraper::{ElementRef, Html, Selector, node::Node}; // `node::Node` is not used in the provided code
-use std::collections::HashSet;
-
-/// Extracts a tweet ID from a given URL href string.
-///
-/// This function looks for the "/status/" segment in the URL and attempts to
-/// extract the numeric ID that follows it.
-///
-/// # Arguments
-/// * `href` - A string slice representing the `href` attribute of a link.
-///
-/// # Returns
-/// `Option<String>`: The extracted tweet ID as a `String` if found, otherwise `None`.
-fn extract_tweet_id_from_href(href: &str) -> Option<String> {
-    href.split("/status/")
-        .nth(1)
-        .map(|s| s.split('/').next().unwrap_or(s).to_string())
-}
+use crate::utils::parse_selector;
+use chrono::Utc;
+use scraper::{ElementRef, Html};
 
 /// Retrieves the value of a specified HTML attribute from an `ElementRef`.
 ///
@@ -36,168 +31,183 @@ fn get_attribute(element: &ElementRef, attr_name: &str) -> Option<String> {
     element.value().attr(attr_name).map(String::from)
 }
 
-/// Processes a `HashSet` of raw HTML strings (representing tweets) into a `Vec` of `Airdrop` structs.
+/// The text of a tweet and the tags and external links in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TweetText {
+    /// The visible text, `None` if the tweet has none.
+    pub text: Option<String>,
+    /// The cashtags, hashtags and `@handles` in the text.
+    pub tags: TweetTags,
+    /// The external links in the text, expanded where the tweet shows where they lead.
+    pub links: Vec<ResolvedLink>,
+}
+
+impl TweetText {
+    /// Reads a `div[data-testid='tweetText']` container.
+    ///
+    /// # Arguments
+    /// * `container` - The text container of the tweet.
+    /// * `tweet_id` - The id of the tweet, if known; links to the tweet itself are skipped.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+    pub fn from_container(container: ElementRef, tweet_id: Option<&str>) -> ScrapeResult<Self> {
+        let link_in_text_selector = parse_selector("a[href]")?;
+
+        // Extract the main text content of the tweet.
+        let text = Some(container.text().collect::<String>().trim().to_owned()).filter(|s| !s.is_empty());
+
+        // Extract the cashtags, hashtags and mentioned users from the anchors X renders for them.
+        let tags = TweetTags::from_container(container)?;
+
+        // Extract all hrefs from <a> tags within the tweet text, with the URL they display.
+        let mut links: Vec<ResolvedLink> = Vec::new();
+        for link_node in container.select(&link_in_text_selector) {
+            if let Some(href) = get_attribute(&link_node, "href") {
+                // Filter for valid HTTP/HTTPS links and exclude links to the tweet itself or hashtags.
+                if (href.starts_with("http://") || href.starts_with("https://"))
+                    && tweet_id.is_none_or(|id| !href.contains(&format!("/status/{}", id)))
+                    && !href.starts_with('#')
+                    && !links.iter().any(|link| link.short == href)
+                {
+                    links.push(ResolvedLink::new(&href, expanded_from_anchor(&link_node)));
+                }
+            }
+        }
+
+        Ok(Self { text, tags, links })
+    }
+}
+
+/// The default `HtmlSetProcessor`, backed by `process_html_set_to_airdrops`.
+pub struct TweetHtmlProcessor;
+
+impl HtmlSetProcessor for TweetHtmlProcessor {
+    fn process_batch(&self, batch: &ScrapedBatch) -> ScrapeResult<Vec<Airdrop>> {
+        process_html_set_to_airdrops(batch)
+    }
+}
+
+/// Processes a `ScrapedBatch` of raw tweet HTML into a `Vec` of `Airdrop` structs.
 ///
-/// This function iterates through each HTML string, parses it, and extracts structured
-/// data relevant to an airdrop, such as tweet ID, text, author, timestamp, mentioned users,
-/// and external links. It expects one of the HTML strings to be an "author_id: <id>" entry
-/// to associate the tweets with a specific author.
+/// This function iterates through each HTML entry of the batch in timeline order, parses it,
+/// and extracts structured data relevant to an airdrop, such as tweet ID, text, author,
+/// timestamp, mentioned users, and external links. The tweet quoted by a quote tweet is read
+/// from its card into the nested `quotedTweet` record. The self-replies of expanded threads
+/// are processed after the timeline and linked to their thread. Every record is attributed to the
+/// batch's `author_id`, except reposts, which are attributed to their author's handle.
 ///
 /// # Arguments
-/// * `html_set` - A reference to a `HashSet` containing raw HTML strings of tweets
-///   and one special string indicating the author ID (e.g., "author_id: 12345").
+/// * `batch` - A reference to the `ScrapedBatch` produced by the scraper.
 ///
 /// # Returns
-/// `Result<Vec<Airdrop>>`: A `Result` containing a vector of `Airdrop` structs on success,
-/// or an `anyhow::Error` if the author ID is not found or parsing fails.
+/// `ScrapeResult<Vec<Airdrop>>`: A `Result` containing a vector of `Airdrop` structs, in the
+/// same order as the batch, on success, or a `ScrapeError` if parsing fails.
 ///
 /// # Errors
-/// * Returns an error if the "author_id: " entry is missing from the `html_set`.
-/// * Returns an error if CSS selectors cannot be parsed.
+/// * Returns `ScrapeError::Parse` if the batch has an empty `author_id`.
+/// * Returns `ScrapeError::InvalidSelector` if CSS selectors cannot be parsed.
 /// * Logs a warning and skips an HTML item if a tweet ID cannot be extracted.
-pub fn process_html_set_to_airdrops(html_set: &HashSet<String>) -> Result<Vec<Airdrop>> {
-    let mut airdrops = Vec::new();
-    let mut extracted_author_id: Option<String> = None;
-
-    // First, try to extract the author_id which is expected to be a special entry in the HashSet.
-    for item_html in html_set {
-        if item_html.starts_with("author_id: ") {
-            extracted_author_id = Some(item_html.trim_start_matches("author_id: ").to_string());
-            break;
-        }
-    }
+pub fn process_html_set_to_airdrops(batch: &ScrapedBatch) -> ScrapeResult<Vec<Airdrop>> {
+    let mut airdrops = Vec::with_capacity(batch.len());
 
-    let author_id = extracted_author_id
-        .ok_or_else(|| anyhow!("author_id entry not found in the provided HashSet"))?;
+    if batch.author_id.trim().is_empty() {
+        return Err(ScrapeError::Parse(format!("batch for {} has no author_id", batch.profile_url)));
+    }
+    let author_id = batch.author_id.clone();
 
     // Define CSS selectors for extracting tweet information.
-    let permalink_selector = Selector::parse("a[href*='/status/']")
-        .map_err(|e| anyhow!("Failed to parse permalink_selector: {}", e))?;
-    let text_selector = Selector::parse("div[data-testid='tweetText']")
-        .map_err(|e| anyhow!("Failed to parse text_selector: {}", e))?;
-    let time_selector = Selector::parse("time[datetime]")
-        .map_err(|e| anyhow!("Failed to parse time_selector: {}", e))?;
-    let link_in_text_selector = Selector::parse("a[href]")
-        .map_err(|e| anyhow!("Failed to parse link_in_text_selector: {}", e))?;
-
-    // Iterate through each HTML string in the set to parse tweet data.
-    for item_html in html_set {
-        // Skip the author_id entry itself, as it's not a tweet HTML.
-        if item_html.starts_with("author_id: ") {
-            continue;
-        }
+    let text_selector = parse_selector("div[data-testid='tweetText']")?;
 
+    // Iterate through each HTML entry of the batch, then of its threads, to parse tweet data.
+    let thread_replies = batch.threads.iter().flat_map(|thread| &thread.replies);
+    for entry in batch.tweets.iter().chain(thread_replies) {
+        let item_html = &entry.html;
         let document = Html::parse_document(item_html);
 
-        let mut tweet_id_opt: Option<String> = None;
-        let mut created_at_opt: Option<DateTime<Utc>> = None;
+        // The tweet ID and creation time are read the same way the scraper reads them while scrolling.
+        let timeline_entry = TimelineEntry::from_document(&document)?;
+        let created_at_opt = timeline_entry.created_at;
 
-        // Attempt to extract tweet ID and creation time from the <time> element and its parent.
-        if let Some(time_element) = document.select(&time_selector).next() {
-            if let Some(datetime_str) = get_attribute(&time_element, "datetime") {
-                created_at_opt = DateTime::parse_from_rfc3339(&datetime_str)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .ok();
-            }
+        // Tell reposts, replies, quotes and the pinned tweet from the profile's own tweets.
+        let relations = TweetRelations::from_document(&document, &timeline_entry)?;
 
-            // Sometimes the permalink is the parent <a> of the <time> element
-            if let Some(parent_node) = time_element.parent() {
-                if let Some(parent_link_element) = ElementRef::wrap(parent_node) {
-                    if parent_link_element.value().name() == "a" {
-                        if let Some(href) = get_attribute(&parent_link_element, "href") {
-                            if let Some(id) = extract_tweet_id_from_href(&href) {
-                                tweet_id_opt = Some(id);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        // If tweet ID was not found via the time element's parent, try other permalinks.
-        if tweet_id_opt.is_none() {
-            for link_el in document.select(&permalink_selector) {
-                if let Some(href) = get_attribute(&link_el, "href") {
-                    // Filter out links that are just for photos/videos within a tweet,
-                    // focusing on the main tweet permalink.
-                    if !href.contains("/photo/") && !href.contains("/video/") {
-                        if let Some(id) = extract_tweet_id_from_href(&href) {
-                            tweet_id_opt = Some(id);
-                            break; // Found the tweet ID, no need to check other links.
-                        }
-                    }
-                }
-            }
-        }
-        
-        let tweet_id = match tweet_id_opt {
-            Some(id) if !id.is_empty() => id,
-            _ => {
+        let tweet_id = match timeline_entry.tweet_id {
+            Some(id) => id,
+            None => {
                 // If tweet ID cannot be determined, log a warning and skip this item.
                 eprintln!(
-                    "Warning: Skipping HTML item due to missing or empty tweet ID. Author: {}. HTML snippet (first 100 chars): {:.100}",
-                    author_id, item_html
+                    "Warning: Skipping HTML item at position {} due to missing or empty tweet ID. Author: {}. HTML snippet (first 100 chars): {:.100}",
+                    entry.position, author_id, item_html
                 );
                 continue;
             }
         };
 
-        // Extract the main text content of the tweet.
-        let text_content: Option<String> = document
-            .select(&text_selector)
-            .next()
-            .map(|el| {
-                el.text().collect::<String>().trim().to_owned()
-            })
-            .filter(|s| !s.is_empty()); // Filter out empty strings
-
-        let mut mentioned_users: Vec<String> = Vec::new();
-        let mut links_in_tweet: Vec<String> = Vec::new();
-
-        // If a text container is found, extract mentioned users and links within the text.
-        if let Some(text_container_el) = document.select(&text_selector).next() {
-            if let Some(ref text_val) = text_content {
-                // Simple extraction of mentioned users by looking for words starting with '@'.
-                mentioned_users = text_val
-                    .split_whitespace()
-                    .filter(|w| w.starts_with('@'))
-                    .map(|w| {
-                        w.trim_matches(|c: char| !c.is_alphanumeric() && c != '@')
-                            .to_string()
-                    })
-                    .filter(|s| !s.is_empty() && s != "@")
-                    .collect();
-            }
+        // The quoted tweet has its own text container inside the quote card; it is read into
+        // a nested record instead of being mixed into this tweet's text, mentions and links.
+        let card = quote_card(&document)?;
+        let tweet_text = match document.select(&text_selector).find(|el| !is_inside(el, card)) {
+            Some(text_container_el) => TweetText::from_container(text_container_el, Some(&tweet_id))?,
+            None => TweetText::default(),
+        };
+        let quoted_tweet = card.map(|card| QuotedTweet::from_card(card, &tweet_id)).transpose()?;
 
-            // Extract all hrefs from <a> tags within the tweet text.
-            for link_node in text_container_el.select(&link_in_text_selector) {
-                if let Some(href) = get_attribute(&link_node, "href") {
-                    // Filter for valid HTTP/HTTPS links and exclude links to the tweet itself or hashtags.
-                    if (href.starts_with("http://") || href.starts_with("https://"))
-                        && !href.contains(&format!("/status/{}", tweet_id))
-                        && !href.starts_with('#')
-                    {
-                        links_in_tweet.push(href);
-                    }
-                }
-            }
-            links_in_tweet.dedup(); // Remove duplicate links.
-        }
+        // Derive the keywords and a token name candidate from the tags and the text.
+        let plain_text = tweet_text.text.as_deref().unwrap_or_default();
+        let keywords = tweet_text.tags.keywords(plain_text);
+        let token_name = tweet_text.tags.token_candidate(plain_text);
+
+        // Extract the like, repost, reply, view and bookmark counts of the action bar.
+        let engagement = Engagement::from_document(&document)?;
+
+        // Extract the attached images, video posters and the link preview card.
+        let media = Media::from_document(&document)?;
+
+        // Reposted tweets are credited to the account that wrote them, not to the profile.
+        let tweet_author_id = match relations.kind {
+            TweetKind::Repost => relations.author_handle.clone(),
+            _ => Some(author_id.clone()),
+        };
 
         // Construct the Airdrop struct and add it to the vector.
         airdrops.push(Airdrop {
             tweetId: tweet_id,
-            text: text_content,
-            authorId: Some(author_id.clone()),
+            text: tweet_text.text,
+            authorId: tweet_author_id,
             createdAt: created_at_opt,
             savedAt: Utc::now(), // Timestamp when the Airdrop was processed.
             deepness: 2, // Hardcoded deepness; consider making this dynamic or configurable.
-            keywords: Vec::new(), // Keywords will likely be populated by further AI analysis.
-            tokenName: None, // Token name will likely be populated by further AI analysis.
-            mentionedUsers: mentioned_users,
-            links: links_in_tweet,
+            keywords, // Deterministic keywords; the AI analysis may add more.
+            tokenName: token_name, // Heuristic candidate; the AI analysis may refine it.
+            links: urls(&tweet_text.links),
+            mentionedUsers: tweet_text.tags.mentions,
+            cashtags: tweet_text.tags.cashtags,
+            hashtags: tweet_text.tags.hashtags,
+            resolvedLinks: tweet_text.links,
+            engagement,
+            tweetType: Some(relations.kind),
+            authorHandle: relations.author_handle,
+            repostedBy: relations.reposted_by,
+            inReplyToTweetId: relations.in_reply_to_tweet_id,
+            inReplyToHandle: relations.in_reply_to_handle,
+            quotedTweetId: relations.quoted_tweet_id.or_else(|| quoted_tweet.as_ref().and_then(|quoted| quoted.tweet_id.clone())),
+            quotedTweet: quoted_tweet,
+            threadRootId: None,
+            threadPosition: None,
+            media,
+            chainAddresses: Vec::new(), // Detected once the links are resolved.
+            relevance: None, // Scored once the addresses are detected.
+            tasks: Vec::new(), // Extracted once the links are resolved.
+            deadlines: Vec::new(), // Extracted with the tasks.
+            nextDeadline: None,
+            rewards: Vec::new(), // Extracted with the tasks.
+            chains: Vec::new(), // Classified once the addresses are detected.
+            riskFlags: Vec::new(), // Checked once the links are resolved.
+            clusterId: None, // Assigned once the records are scored.
         });
     }
 
+    link_threads(batch, &mut airdrops);
     Ok(airdrops)
 }
\ No newline at end of file
@@ -0,0 +1,489 @@
+//! This module flags the links of a record that may lead to phishing or wallet-drainer
+//! sites, so they are never shown to users as if they were safe.
+//!
+//! `LinkSafetyChecker` sets `riskFlags` on every link and, as their union, on the record:
+//! - `blocklisted`: the domain, or a parent of it, is in the local blocklist file.
+//! - `lookalike`: the domain imitates an official one: a typo (`uniswqp.org`), digit swaps
+//!   (`un1swap.org`), another TLD (`uniswap.top`) or the official name in a foreign domain
+//!   (`uniswap-claim.com`, `jup.ag.claim.xyz`).
+//! - `homoglyph`: an internationalized (`xn--`) domain that reads as an official one once its
+//!   lookalike characters, such as the Cyrillic `а`, are mapped to Latin.
+//! - `freshDomain`: the domain was first seen only days ago; drainer sites rarely live long.
+//!   Domains met on the first run, before any were recorded, are not new to the web, only to
+//!   the store, and are never flagged.
+//! - `suspiciousTld`: the domain is under a TLD mostly used for throwaway sites.
+//! - `walletPrompt`: an unofficial domain whose URL, or the step of the tweet showing the
+//!   link, asks to claim, connect or verify a wallet.
+//!
+//! Official domains are the chain data domains (explorers and dapps) and the configured
+//! platforms and projects. The `domains` stored with a watched profile are official too, but
+//! only in the profile's own tweets: anyone can name themselves after a project, so nothing
+//! the author writes makes a domain trusted. Domains are compared by their registrable part,
+//! e.g. `uniswap.org` for `app.uniswap.org`.
+
+use crate::config::LinkSafetyConfig;
+use crate::processing::links::ResolvedLink;
+use crate::processing::tasks::{mentions_link, steps};
+use crate::scraper::user_profile::Airdrop;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+};
+use url::Url;
+
+/// A reason to distrust a link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RiskFlag {
+    /// The domain is in the blocklist.
+    Blocklisted,
+    /// The domain imitates an official domain.
+    Lookalike,
+    /// The domain imitates an official domain with lookalike Unicode characters.
+    Homoglyph,
+    /// The domain was first seen within the last `fresh_days`.
+    FreshDomain,
+    /// The domain is under a TLD mostly used for throwaway sites.
+    SuspiciousTld,
+    /// A wallet claim or connect prompt leads to an unofficial domain.
+    WalletPrompt,
+}
+
+/// Second-level labels under which a country TLD registers domains, as in `example.co.uk`.
+const SECOND_LEVEL_LABELS: [&str; 7] = ["co", "com", "net", "org", "gov", "edu", "ac"];
+
+/// Phrases of a tweet that ask to act with a wallet, lower-cased.
+const WALLET_PHRASES: [&str; 9] = [
+    "claim",
+    "connect wallet",
+    "connect your wallet",
+    "check eligibility",
+    "check your eligibility",
+    "verify your wallet",
+    "validate your wallet",
+    "sync your wallet",
+    "revoke",
+];
+
+/// Word prefixes of a URL path or query that lead to a wallet prompt.
+const WALLET_PATH_WORDS: [&str; 8] = ["claim", "airdrop", "connect", "wallet", "eligib", "reward", "verify", "revoke"];
+
+/// Flags risky links against the blocklist, the official domains and the domains seen before.
+pub struct LinkSafetyChecker {
+    enabled: bool,
+    blocklist: HashSet<String>,
+    suspicious_tlds: HashSet<String>,
+    /// Registrable official domains, e.g. `uniswap.org`.
+    official: HashSet<String>,
+    fresh_days: u32,
+    seen_path: PathBuf,
+    seen: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl LinkSafetyChecker {
+    /// Creates a checker from the `[link_safety]` settings and loads the blocklist and the
+    /// first-seen times of the domains. A missing or unreadable file only prints a warning.
+    ///
+    /// # Arguments
+    /// * `config` - The `[link_safety]` settings.
+    /// * `chain_domains` - The domains of the chain data, which count as official.
+    pub fn new<'a>(config: &LinkSafetyConfig, chain_domains: impl IntoIterator<Item = &'a str>) -> Self {
+        let blocklist = match fs::read_to_string(&config.blocklist_path) {
+            Ok(contents) => contents
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or_default().trim().trim_start_matches("www.").to_lowercase())
+                .filter(|domain| !domain.is_empty())
+                .collect(),
+            Err(e) => {
+                println!("Warning: Failed to read the link blocklist {:?}: {}", config.blocklist_path, e);
+                HashSet::new()
+            }
+        };
+        let seen = fs::read_to_string(&config.seen_domains_path)
+            .ok()
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(seen) => Some(seen),
+                Err(e) => {
+                    println!("Ignoring unreadable seen domains file {:?}: {}", config.seen_domains_path, e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let official = config
+            .official_domains
+            .iter()
+            .map(|domain| domain.trim().to_lowercase())
+            .chain(chain_domains.into_iter().map(str::to_lowercase))
+            .map(|domain| registrable(&domain).to_string())
+            .collect();
+        Self {
+            enabled: config.enabled,
+            blocklist,
+            suspicious_tlds: config.suspicious_tlds.iter().map(|tld| tld.trim_start_matches('.').to_lowercase()).collect(),
+            official,
+            fresh_days: config.fresh_days,
+            seen_path: config.seen_domains_path.clone(),
+            seen: Mutex::new(seen),
+        }
+    }
+
+    /// Flags the links of every record and sets the record's `riskFlags` to their union, then
+    /// saves the first-seen times if new domains appeared. Run this after the shortlinks are
+    /// resolved, so the destination domains are checked.
+    ///
+    /// # Arguments
+    /// * `airdrops` - The records scraped from one profile.
+    /// * `profile_handle` - The handle of the watched profile the records were scraped from.
+    /// * `profile_domains` - The domains the profile's project owns, trusted only in tweets
+    ///   the profile wrote itself.
+    ///
+    /// # Returns
+    /// `usize`: The number of flagged links.
+    pub fn check_airdrops(&self, airdrops: &mut [Airdrop], profile_handle: &str, profile_domains: &[String]) -> usize {
+        if !self.enabled {
+            return 0;
+        }
+        let seen_before = self.lock_seen().len();
+        let first_run = seen_before == 0;
+        let now = Utc::now();
+        let profile_handle = profile_handle.trim_start_matches('@');
+        let profile_sites: Vec<String> =
+            profile_domains.iter().map(|domain| registrable(&domain.trim().to_lowercase()).to_string()).collect();
+        let mut flagged = 0;
+        for airdrop in airdrops.iter_mut() {
+            let text = airdrop.text.as_deref().unwrap_or_default().to_lowercase();
+            let own_tweet = airdrop
+                .authorHandle
+                .as_deref()
+                .is_some_and(|handle| handle.trim_start_matches('@').eq_ignore_ascii_case(profile_handle));
+            let trusted_sites: &[String] = if own_tweet { &profile_sites } else { &[] };
+            let mut record_flags: Vec<RiskFlag> = Vec::new();
+            for link in airdrop.resolvedLinks.iter_mut() {
+                link.risk_flags = self.check_link(link, &text, trusted_sites, now, first_run);
+                flagged += usize::from(!link.risk_flags.is_empty());
+                for flag in &link.risk_flags {
+                    if !record_flags.contains(flag) {
+                        record_flags.push(*flag);
+                    }
+                }
+            }
+            airdrop.riskFlags = record_flags;
+        }
+        if self.lock_seen().len() > seen_before {
+            self.save();
+        }
+        flagged
+    }
+
+    /// Returns the risk flags of a single link and records its domain as seen.
+    ///
+    /// # Arguments
+    /// * `link` - The link, expanded where possible. Unresolved shortlinks are not checked.
+    /// * `text` - The lower-cased tweet text; the step showing the link is searched for
+    ///   wallet prompts.
+    /// * `trusted_sites` - Registrable domains that count as official for this link, e.g. the
+    ///   watched profile's own domains in its own tweets.
+    /// * `now` - The time the domain is recorded as seen at.
+    /// * `first_run` - Whether no domain had been seen before this batch, in which case the
+    ///   domain is not flagged as fresh.
+    pub fn check_link(
+        &self,
+        link: &ResolvedLink,
+        text: &str,
+        trusted_sites: &[String],
+        now: DateTime<Utc>,
+        first_run: bool,
+    ) -> Vec<RiskFlag> {
+        let Some(domain) = link.expanded.as_ref().and(link.domain.as_deref()) else {
+            return Vec::new();
+        };
+        let site = registrable(domain);
+        let mut flags = Vec::new();
+
+        if domain_and_parents(domain).any(|candidate| self.blocklist.contains(candidate)) {
+            flags.push(RiskFlag::Blocklisted);
+        }
+        if self.official.contains(site) || trusted_sites.iter().any(|trusted| trusted == site) {
+            return flags;
+        }
+
+        if domain.split('.').any(|label| label.starts_with("xn--")) {
+            let (unicode, _) = idna::domain_to_unicode(domain);
+            if self.imitated(&unicode).is_some() {
+                flags.push(RiskFlag::Homoglyph);
+            }
+        } else if self.imitated(domain).is_some() {
+            flags.push(RiskFlag::Lookalike);
+        }
+        if self.is_fresh(site, now, first_run) {
+            flags.push(RiskFlag::FreshDomain);
+        }
+        if domain.rsplit('.').next().is_some_and(|tld| self.suspicious_tlds.contains(tld)) {
+            flags.push(RiskFlag::SuspiciousTld);
+        }
+        if step_asks_for_wallet(text, link) || url_asks_for_wallet(link.url()) {
+            flags.push(RiskFlag::WalletPrompt);
+        }
+        flags
+    }
+
+    /// Returns the official domain that `domain` imitates, if any. An official domain imitates
+    /// none, even when it shares its name with another one (`zora.energy` and `zora.co`).
+    fn imitated(&self, domain: &str) -> Option<&str> {
+        let site = registrable(domain);
+        if self.official.contains(site) {
+            return None;
+        }
+        let label = site.split('.').next().unwrap_or_default();
+        let label_skeleton = skeleton(label);
+        let token_skeletons: Vec<String> = domain.split(['.', '-']).map(skeleton).collect();
+
+        self.official.iter().map(String::as_str).find(|official| {
+            let official_label = official.split('.').next().unwrap_or_default();
+            let official_skeleton = skeleton(official_label);
+            let length = official_label.chars().count();
+            let max_distance = if length >= 9 { 2 } else { 1 };
+            (length >= 4 && label == official_label)
+                || (length >= 3 && label_skeleton == official_skeleton)
+                || (length >= 5 && edit_distance(&label_skeleton, &official_skeleton) <= max_distance)
+                || (length >= 4 && token_skeletons.contains(&official_skeleton))
+                // The whole official domain in front of another one, `jup.ag.claim.xyz`.
+                || domain.starts_with(&format!("{}.", official))
+                || domain.contains(&format!(".{}.", official))
+        })
+    }
+
+    /// Returns `true` if `site` was first seen less than `fresh_days` ago, recording it as
+    /// seen `now` on its first appearance. On the first run the store is only being filled,
+    /// so a new site is recorded as seen `fresh_days` ago and is not fresh then or later.
+    fn is_fresh(&self, site: &str, now: DateTime<Utc>, first_run: bool) -> bool {
+        let recorded = if first_run { now - Duration::days(i64::from(self.fresh_days)) } else { now };
+        let first_seen = *self.lock_seen().entry(site.to_string()).or_insert(recorded);
+        self.fresh_days > 0 && now - first_seen < Duration::days(i64::from(self.fresh_days))
+    }
+
+    /// Writes the first-seen times next to their file and renames it into place.
+    fn save(&self) {
+        let contents = match serde_json::to_string_pretty(&*self.lock_seen()) {
+            Ok(contents) => contents,
+            Err(e) => {
+                println!("Warning: Failed to serialize the seen domains: {}", e);
+                return;
+            }
+        };
+        let temporary = self.seen_path.with_extension("json.tmp");
+        if let Err(e) = fs::write(&temporary, contents).and_then(|()| fs::rename(&temporary, &self.seen_path)) {
+            println!("Warning: Failed to save the seen domains {:?}: {}", self.seen_path, e);
+        }
+    }
+
+    /// Locks the first-seen times, recovering them if a panicking thread poisoned the lock.
+    fn lock_seen(&self) -> std::sync::MutexGuard<'_, HashMap<String, DateTime<Utc>>> {
+        self.seen.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Returns the registrable part of a domain: its last two labels, or three under a
+/// second-level label such as `co.uk`.
+fn registrable(domain: &str) -> &str {
+    let labels: Vec<&str> = domain.split('.').collect();
+    let count = labels.len();
+    let keep = if count >= 3 && labels[count - 1].len() == 2 && SECOND_LEVEL_LABELS.contains(&labels[count - 2]) {
+        3
+    } else {
+        2
+    };
+    if count <= keep {
+        return domain;
+    }
+    let start: usize = labels[..count - keep].iter().map(|label| label.len() + 1).sum();
+    &domain[start..]
+}
+
+/// Returns `domain` and each of its parent domains, e.g. `a.b.com`, `b.com` and `com`.
+fn domain_and_parents(domain: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(domain), |domain| domain.split_once('.').map(|(_, parent)| parent))
+}
+
+/// Maps the characters of a domain label that look alike to one representative, so
+/// `un1swap`, `uniswap` and `unіswap` (with a Cyrillic `і`) read the same.
+fn skeleton(label: &str) -> String {
+    let mapped: String = label
+        .chars()
+        .filter(|c| *c != '-')
+        .map(|c| match c {
+            '0' | 'о' | 'ο' | 'σ' => 'o',
+            '1' | 'i' | 'l' | '|' | 'і' | 'ӏ' | 'ι' => 'l',
+            '3' | 'е' | 'ё' | 'ε' => 'e',
+            '5' | 'ѕ' => 's',
+            'а' | 'α' => 'a',
+            'с' | 'ϲ' => 'c',
+            'р' | 'ρ' => 'p',
+            'у' | 'γ' => 'y',
+            'х' | 'χ' => 'x',
+            'ј' => 'j',
+            'ԁ' => 'd',
+            'һ' => 'h',
+            'к' | 'κ' => 'k',
+            'ν' => 'v',
+            'ԝ' | 'ω' => 'w',
+            'τ' | 'т' => 't',
+            'ո' | 'η' => 'n',
+            'ɡ' => 'g',
+            'ԛ' => 'q',
+            'в' => 'b',
+            'м' => 'm',
+            other => other,
+        })
+        .collect();
+    mapped.replace("rn", "m").replace("vv", "w")
+}
+
+/// Returns the optimal string alignment distance: the insertions, deletions, substitutions
+/// and swaps of adjacent characters that turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Returns `true` if the step of the lower-cased tweet text showing `link` asks to claim or
+/// act with a wallet. A step that is nothing but the link ("Claim here 👇" followed by the
+/// URL on its own line) is read together with the step before it.
+fn step_asks_for_wallet(text: &str, link: &ResolvedLink) -> bool {
+    let steps = steps(text);
+    let Some(index) = steps.iter().position(|step| mentions_link(step, link)) else {
+        return false;
+    };
+    let mut rest = steps[index].replace(&link.short.to_lowercase(), "").replace(&link.url().to_lowercase(), "");
+    if let Some(domain) = link.domain.as_deref() {
+        rest = rest.replace(domain, "");
+    }
+    let bare = rest
+        .chars()
+        .all(|c| !c.is_alphanumeric());
+    let first = if bare { index.saturating_sub(1) } else { index };
+    steps[first..=index].iter().any(|step| asks_for_wallet(step))
+}
+
+/// Returns `true` if the lower-cased text asks to claim or act with a wallet.
+fn asks_for_wallet(text: &str) -> bool {
+    WALLET_PHRASES.iter().any(|phrase| {
+        text.match_indices(phrase)
+            .any(|(index, _)| text[..index].chars().next_back().is_none_or(|c| !c.is_alphanumeric()))
+    })
+}
+
+/// Returns `true` if the path or query of `url` leads to a claim or wallet page.
+fn url_asks_for_wallet(url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+    let rest = format!("{}?{}", url.path(), url.query().unwrap_or_default()).to_lowercase();
+    rest.split(|c: char| !c.is_alphanumeric())
+        .any(|word| WALLET_PATH_WORDS.iter().any(|prefix| word.starts_with(prefix)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ChainsConfig;
+    use crate::processing::chains::ChainClassifier;
+
+    /// A checker with the default official domains and the built-in chain data, and no
+    /// blocklist or seen domains.
+    fn checker() -> LinkSafetyChecker {
+        let chains = ChainClassifier::load(&ChainsConfig::default()).expect("built-in chain data");
+        let config = LinkSafetyConfig {
+            blocklist_path: PathBuf::from("./missing-blocklist.txt"),
+            seen_domains_path: PathBuf::from("./missing-seen-domains.json"),
+            ..LinkSafetyConfig::default()
+        };
+        LinkSafetyChecker::new(&config, chains.domains())
+    }
+
+    #[test]
+    fn finds_registrable_domains() {
+        let cases = [
+            ("app.uniswap.org", "uniswap.org"),
+            ("uniswap.org", "uniswap.org"),
+            ("jup.ag", "jup.ag"),
+            ("claim.example.co.uk", "example.co.uk"),
+            ("localhost", "localhost"),
+        ];
+        for (domain, expected) in cases {
+            assert_eq!(registrable(domain), expected, "{}", domain);
+        }
+    }
+
+    #[test]
+    fn maps_lookalike_characters() {
+        assert_eq!(skeleton("un1swap"), skeleton("uniswap"));
+        assert_eq!(skeleton("unіswap"), skeleton("uniswap"), "Cyrillic і");
+        assert_eq!(skeleton("rnetamask"), skeleton("metamask"));
+        assert_eq!(skeleton("0pensea"), skeleton("opensea"));
+        assert_ne!(skeleton("unswap"), skeleton("uniswap"));
+    }
+
+    #[test]
+    fn flags_imitated_domains() {
+        let checker = checker();
+        let cases = [
+            ("un1swap.org", Some("uniswap.org")),
+            ("uniswqp.org", Some("uniswap.org")),
+            ("uniswap.top", Some("uniswap.org")),
+            ("uniswap-claim.com", Some("uniswap.org")),
+            ("jup.ag.claim.xyz", Some("jup.ag")),
+            ("uniswap.org", None),
+            ("app.uniswap.org", None),
+            ("zora.energy", None),
+            ("example.com", None),
+            ("database-tools.com", None),
+        ];
+        for (domain, expected) in cases {
+            assert_eq!(checker.imitated(domain), expected, "{}", domain);
+        }
+    }
+
+    #[test]
+    fn flags_risky_links() {
+        let checker = checker();
+        let now = Utc::now();
+        let flags = |url: &str, text: &str, trusted_sites: &[String]| {
+            checker.check_link(&ResolvedLink::new(url, None), text, trusted_sites, now, true)
+        };
+        let homoglyph = format!("https://{}/", idna::domain_to_ascii("unіswap.org").unwrap());
+        assert!(homoglyph.contains("xn--"));
+
+        assert_eq!(flags(&homoglyph, "", &[]), vec![RiskFlag::Homoglyph]);
+        assert_eq!(flags("https://un1swap.org/", "", &[]), vec![RiskFlag::Lookalike]);
+        assert_eq!(flags("https://jup.ag.claim.xyz/", "", &[]), vec![RiskFlag::Lookalike]);
+        assert_eq!(flags("https://zora.energy/", "", &[]), Vec::<RiskFlag>::new());
+        // A handle named after the domain does not make a wallet prompt on it trusted.
+        assert_eq!(flags("https://claimdrop.xyz/connect", "connect your wallet at claimdrop.xyz/connect", &[]), vec![RiskFlag::WalletPrompt]);
+        // The watched profile's own stored domain does.
+        let own = ["claimdrop.xyz".to_string()];
+        assert_eq!(flags("https://app.claimdrop.xyz/connect", "connect your wallet at app.claimdrop.xyz/connect", &own), Vec::<RiskFlag>::new());
+    }
+}
@@ -0,0 +1,194 @@
+//! This module turns the links of a tweet into `ResolvedLink`s: the `t.co` shortlink X puts
+//! in the `href`, the URL it leads to and that URL's domain.
+//!
+//! The expanded URL is taken from the tweet itself whenever possible: the GraphQL
+//! `expanded_url`, or the link's `title` and text in the HTML, which X renders as the full
+//! URL with the overflow hidden. Only the remaining shortlinks are resolved by
+//! `LinkResolver`, which follows their redirects with `HEAD` requests and caches the result
+//! on disk, since a shortlink always leads to the same place.
+
+use crate::config::LinksConfig;
+use crate::processing::link_safety::RiskFlag;
+use crate::scraper::user_profile::Airdrop;
+use scraper::ElementRef;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+};
+use url::Url;
+
+/// A link of a tweet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedLink {
+    /// The URL as it appears in the tweet, usually a `t.co` shortlink.
+    pub short: String,
+    /// The URL the link leads to, `None` while a shortlink is unresolved.
+    pub expanded: Option<String>,
+    /// The host of the expanded URL without a leading `www.`, e.g. `example.com`.
+    pub domain: Option<String>,
+    /// Reasons to distrust the link, set by `LinkSafetyChecker`.
+    #[serde(default)]
+    pub risk_flags: Vec<RiskFlag>,
+}
+
+impl ResolvedLink {
+    /// Creates a link, deriving its domain from the expanded URL. A link that is not a
+    /// shortlink is its own expansion.
+    pub fn new(short: &str, expanded: Option<String>) -> Self {
+        let expanded = expanded.or_else(|| (!is_shortlink(short)).then(|| short.to_string()));
+        Self {
+            short: short.to_string(),
+            domain: expanded.as_deref().and_then(domain_of),
+            expanded,
+            risk_flags: Vec::new(),
+        }
+    }
+
+    /// Returns the expanded URL, or the shortlink while it is unresolved.
+    pub fn url(&self) -> &str {
+        self.expanded.as_deref().unwrap_or(&self.short)
+    }
+}
+
+/// Returns the URL of every link, expanded where known.
+pub fn urls(links: &[ResolvedLink]) -> Vec<String> {
+    links.iter().map(|link| link.url().to_string()).collect()
+}
+
+/// Reads the expanded URL of a link in the tweet HTML from its `title`, or from its text,
+/// which holds the full URL with an ellipsis appended when X truncates the visible part.
+///
+/// # Returns
+/// `Option<String>`: `None` if neither is a complete URL.
+pub fn expanded_from_anchor(anchor: &ElementRef) -> Option<String> {
+    let title = anchor.value().attr("title").map(str::trim);
+    let text = anchor.text().collect::<String>();
+    let text = text.trim().trim_end_matches('…');
+    title
+        .into_iter()
+        .chain([text])
+        .find(|candidate| (candidate.starts_with("http://") || candidate.starts_with("https://")) && Url::parse(candidate).is_ok())
+        .map(String::from)
+}
+
+/// Returns `true` for a `t.co` shortlink.
+pub fn is_shortlink(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| url.host_str() == Some("t.co"))
+}
+
+/// Returns the host of `url` without a leading `www.`.
+fn domain_of(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    Some(host.strip_prefix("www.").unwrap_or(host).to_lowercase())
+}
+
+/// Resolves `t.co` shortlinks by following their redirects, with a cache kept on disk.
+pub struct LinkResolver {
+    agent: Option<ureq::Agent>,
+    cache_path: PathBuf,
+    cache: Mutex<HashMap<String, String>>,
+}
+
+impl LinkResolver {
+    /// Creates a resolver and loads its cache from `config.cache_path`. With
+    /// `config.resolve` disabled, only cached shortlinks are resolved.
+    pub fn new(config: &LinksConfig) -> Self {
+        let cache = fs::read_to_string(&config.cache_path)
+            .ok()
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    println!("Ignoring unreadable link cache {:?}: {}", config.cache_path, e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let agent = config.resolve.then(|| {
+            ureq::AgentBuilder::new()
+                .timeout(config.timeout())
+                .redirects(config.max_redirects)
+                .build()
+        });
+        Self {
+            agent,
+            cache_path: config.cache_path.clone(),
+            cache: Mutex::new(cache),
+        }
+    }
+
+    /// Returns the URL a shortlink leads to, from the cache or by following its redirects.
+    ///
+    /// # Returns
+    /// `Option<String>`: `None` if `short` is not a shortlink or cannot be resolved. Failures
+    /// are not cached, so the next batch tries again.
+    pub fn resolve(&self, short: &str) -> Option<String> {
+        if !is_shortlink(short) {
+            return None;
+        }
+        if let Some(expanded) = self.lock_cache().get(short) {
+            return Some(expanded.clone());
+        }
+        let agent = self.agent.as_ref()?;
+        // The destination may refuse `HEAD` or error; the final URL is what matters.
+        let final_url = match agent.head(short).call() {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response.get_url().to_string(),
+            Err(e) => {
+                println!("Warning: Failed to resolve {}: {}", short, e);
+                return None;
+            }
+        };
+        if is_shortlink(&final_url) {
+            return None;
+        }
+        self.lock_cache().insert(short.to_string(), final_url.clone());
+        Some(final_url)
+    }
+
+    /// Resolves the unexpanded links of every record and rewrites its `links` with the
+    /// expanded URLs, then saves the cache if it has grown.
+    pub fn resolve_airdrops(&self, airdrops: &mut [Airdrop]) {
+        let cached_before = self.lock_cache().len();
+        let mut resolved = 0;
+        for airdrop in airdrops.iter_mut() {
+            for link in airdrop.resolvedLinks.iter_mut().filter(|link| link.expanded.is_none()) {
+                if let Some(expanded) = self.resolve(&link.short) {
+                    *link = ResolvedLink::new(&link.short, Some(expanded));
+                    resolved += 1;
+                }
+            }
+            airdrop.links = urls(&airdrop.resolvedLinks);
+            airdrop.links.dedup();
+        }
+        if resolved > 0 {
+            println!("Resolved {} shortlinks.", resolved);
+        }
+        if self.lock_cache().len() > cached_before {
+            self.save();
+        }
+    }
+
+    /// Writes the cache next to its file and renames it into place.
+    fn save(&self) {
+        let contents = match serde_json::to_string_pretty(&*self.lock_cache()) {
+            Ok(contents) => contents,
+            Err(e) => {
+                println!("Warning: Failed to serialize the link cache: {}", e);
+                return;
+            }
+        };
+        let temporary = self.cache_path.with_extension("json.tmp");
+        if let Err(e) = fs::write(&temporary, contents).and_then(|()| fs::rename(&temporary, &self.cache_path)) {
+            println!("Warning: Failed to save the link cache {:?}: {}", self.cache_path, e);
+        }
+    }
+
+    /// Locks the cache, recovering it if a panicking thread poisoned the lock.
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, HashMap<String, String>> {
+        self.cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,177 @@
+//! This module extracts the media attached to a tweet: images, video and GIF posters with
+//! their alt text, and the preview card of a shared link.
+//!
+//! Many airdrop announcements are infographics, so the AI step needs the image URLs and the
+//! alt text as much as the tweet text. `Media::from_document` reads the rendered HTML; the
+//! GraphQL processor fills the same struct from `extended_entities` and `card`.
+
+use crate::errors::ScrapeResult;
+use crate::processing::quoted_tweet::{is_inside, quote_card};
+use crate::utils::parse_selector;
+use scraper::{ElementRef, Html};
+use serde::{Deserialize, Serialize};
+
+/// The kind of an attached media item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    /// A photo; `url` is the image itself.
+    Photo,
+    /// A video; `url` is its poster image.
+    Video,
+    /// An animated GIF; `url` is its poster image.
+    Gif,
+}
+
+/// An image, video or GIF attached to a tweet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaItem {
+    /// What the item is.
+    pub kind: MediaKind,
+    /// The `pbs.twimg.com` URL of the image, or of the poster for videos and GIFs.
+    pub url: String,
+    /// The alt text the author wrote for the item.
+    pub alt_text: Option<String>,
+}
+
+/// The preview card X renders for a shared link.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCard {
+    /// The link the card opens, usually a `t.co` URL.
+    pub url: Option<String>,
+    /// The page title.
+    pub title: Option<String>,
+    /// The page description.
+    pub description: Option<String>,
+    /// The domain of the linked page, e.g. `example.com`.
+    pub domain: Option<String>,
+    /// The `pbs.twimg.com` URL of the card's preview image.
+    pub image_url: Option<String>,
+}
+
+/// The media and link card of a tweet.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Media {
+    /// Attached images, videos and GIFs, in display order.
+    pub items: Vec<MediaItem>,
+    /// The preview card of a shared link.
+    pub card: Option<LinkCard>,
+}
+
+/// Alt texts X shows when the author wrote none.
+const PLACEHOLDER_ALT_TEXTS: [&str; 3] = ["Image", "Embedded video", "GIF"];
+
+impl Media {
+    /// Reads the media of a parsed tweet `article`. Media of a quoted tweet is skipped.
+    ///
+    /// # Returns
+    /// `ScrapeResult<Option<Media>>`: `None` if the tweet has neither media nor a link card.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+    pub fn from_document(document: &Html) -> ScrapeResult<Option<Self>> {
+        let photo_selector = parse_selector("div[data-testid='tweetPhoto'] img[src*='pbs.twimg.com/media/']")?;
+        let video_selector = parse_selector("div[data-testid='videoPlayer'] video[poster]")?;
+        let card_selector = parse_selector("div[data-testid='card.wrapper']")?;
+
+        let quote = quote_card(document)?;
+        let mut media = Self::default();
+        for photo in document.select(&photo_selector).filter(|el| !is_inside(el, quote)) {
+            if let Some(src) = photo.value().attr("src") {
+                media.push(MediaKind::Photo, &large_image_url(src), alt_text(photo.value().attr("alt")));
+            }
+        }
+        for video in document.select(&video_selector).filter(|el| !is_inside(el, quote)) {
+            if let Some(poster) = video.value().attr("poster") {
+                // GIFs are served as looping videos with a `tweet_video_thumb` poster.
+                let kind = if poster.contains("tweet_video_thumb") { MediaKind::Gif } else { MediaKind::Video };
+                media.push(kind, poster, alt_text(video.value().attr("aria-label")));
+            }
+        }
+        media.card = match document.select(&card_selector).find(|el| !is_inside(el, quote)) {
+            Some(card) => Some(link_card(card)?),
+            None => None,
+        };
+
+        Ok((media != Self::default()).then_some(media))
+    }
+
+    /// Appends an item unless the same URL was already added.
+    pub fn push(&mut self, kind: MediaKind, url: &str, alt_text: Option<String>) {
+        if !self.items.iter().any(|item| item.url == url) {
+            self.items.push(MediaItem {
+                kind,
+                url: url.to_string(),
+                alt_text,
+            });
+        }
+    }
+}
+
+/// Reads a link preview card.
+///
+/// Small cards show the domain, title and description as consecutive text blocks; large
+/// cards show the title over the image and the domain as "From example.com" below it.
+fn link_card(card: ElementRef) -> ScrapeResult<LinkCard> {
+    let link_selector = parse_selector("a[href]")?;
+    let detail_selector = parse_selector("div[data-testid$='.detail'] span")?;
+    let image_selector = parse_selector("img[src*='pbs.twimg.com/card_img/']")?;
+
+    let texts: Vec<String> = card
+        .select(&detail_selector)
+        .filter(|span| span.children().all(|child| child.value().is_text()))
+        .map(|span| span.text().collect::<String>().trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect();
+    let mut domain = None;
+    let mut rest = Vec::new();
+    for text in texts {
+        match text.strip_prefix("From ") {
+            Some(from) if domain.is_none() => domain = Some(from.to_string()),
+            _ if domain.is_none() && rest.is_empty() && looks_like_domain(&text) => domain = Some(text),
+            _ => rest.push(text),
+        }
+    }
+    let mut rest = rest.into_iter();
+
+    Ok(LinkCard {
+        url: card.select(&link_selector).next().and_then(|link| link.value().attr("href")).map(String::from),
+        title: rest.next(),
+        description: rest.next(),
+        domain,
+        image_url: card.select(&image_selector).next().and_then(|img| img.value().attr("src")).map(String::from),
+    })
+}
+
+/// Returns `true` for a bare host name such as `example.com`.
+fn looks_like_domain(text: &str) -> bool {
+    text.contains('.')
+        && !text.contains(char::is_whitespace)
+        && text.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+}
+
+/// Requests the large rendition of a `pbs.twimg.com` photo instead of the thumbnail the
+/// timeline displays (`name=small`, `name=360x360`, ...).
+fn large_image_url(src: &str) -> String {
+    match src.split_once('?') {
+        Some((path, query)) => {
+            let params: Vec<&str> = query
+                .split('&')
+                .map(|param| if param.starts_with("name=") { "name=large" } else { param })
+                .collect();
+            format!("{}?{}", path, params.join("&"))
+        }
+        None => src.to_string(),
+    }
+}
+
+/// Returns the alt text, dropping the placeholders X shows when the author wrote none.
+fn alt_text(label: Option<&str>) -> Option<String> {
+    label
+        .map(str::trim)
+        .filter(|label| !label.is_empty() && !PLACEHOLDER_ALT_TEXTS.contains(label))
+        .map(String::from)
+}
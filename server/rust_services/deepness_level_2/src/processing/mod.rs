@@ -4,6 +4,51 @@
 /// Provides functionality to process raw HTML data into structured data models.
 pub mod html_processor;
 
+/// Processes captured GraphQL timeline responses, with the HTML as the fallback.
+pub mod graphql_processor;
+
+/// Extracts engagement metrics (likes, reposts, replies, views, bookmarks) from tweet HTML.
+pub mod engagement;
+
+/// Classifies tweets as original, repost, reply, quote or pinned and records their relations.
+pub mod classification;
+
+/// Extracts the tweet embedded in a quote tweet as a nested record.
+pub mod quoted_tweet;
+
+/// Links the records of self-reply threads and joins their text.
+pub mod thread;
+
+/// Extracts attached images, videos, GIFs and link preview cards.
+pub mod media;
+
+/// Expands `t.co` shortlinks into their destination URL and domain.
+pub mod links;
+
+/// Extracts cashtags, hashtags, mentions, keywords and a token name candidate.
+pub mod tags;
+
+/// Detects EVM and Solana addresses and ENS / SNS names with a guess of their chain.
+pub mod addresses;
+
+/// Scores records for airdrop relevance with configurable rules and drops unrelated ones.
+pub mod relevance;
+
+/// Extracts the checklist of tasks an airdrop tweet asks for (follow, repost, join, bridge...).
+pub mod tasks;
+
+/// Extracts snapshot, claim and end dates, resolving relative ones against `createdAt`.
+pub mod deadlines;
+
+/// Extracts reward amounts with their unit and token.
+pub mod rewards;
+/// Classifies records by chain and ecosystem from a chains data file.
+pub mod chains;
+/// Flags phishing and drainer links: blocklisted, lookalike, fresh and wallet-prompt domains.
+pub mod link_safety;
+/// Groups near-duplicate tweets across authors and batches into clusters.
+pub mod clustering;
+
 /// Defines traits for data processing, enabling interchangeable processing strategies.
 pub mod traits;
 
@@ -0,0 +1,105 @@
+//! This module extracts the tweet embedded in a quote tweet as a record of its own.
+//!
+//! X renders the quoted tweet as a clickable card inside the quoting tweet's `article`, with
+//! its own `User-Name`, `<time>` and `tweetText`. The card is read separately so that its
+//! text and links are not mixed into the quoting tweet, and is stored nested in the quoting
+//! tweet's `Airdrop` as `quotedTweet`, linked back through `parentTweetId`.
+
+use crate::errors::ScrapeResult;
+use crate::processing::html_processor::TweetText;
+use crate::processing::links::urls;
+use crate::utils::{handle_from_href, parse_selector, tweet_id_from_href};
+use chrono::{DateTime, Utc};
+use scraper::{ElementRef, Html};
+use serde::{Deserialize, Serialize};
+
+/// A tweet quoted by another tweet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotedTweet {
+    /// The quoted tweet's id. The rendered card does not always link to it.
+    pub tweet_id: Option<String>,
+    /// Id of the tweet quoting it.
+    pub parent_tweet_id: String,
+    /// Handle of the account that wrote the quoted tweet.
+    pub author_handle: Option<String>,
+    /// The quoted tweet's text.
+    pub text: Option<String>,
+    /// When the quoted tweet was posted.
+    pub created_at: Option<DateTime<Utc>>,
+    /// Users mentioned in the quoted tweet's text.
+    pub mentioned_users: Vec<String>,
+    /// External links in the quoted tweet's text, expanded where known.
+    pub links: Vec<String>,
+}
+
+impl QuotedTweet {
+    /// Reads a quoted tweet from its card, see `quote_card`.
+    ///
+    /// # Arguments
+    /// * `card` - The quote card inside the quoting tweet's `article`.
+    /// * `parent_tweet_id` - The id of the quoting tweet.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+    pub fn from_card(card: ElementRef, parent_tweet_id: &str) -> ScrapeResult<Self> {
+        let user_name_selector = parse_selector("div[data-testid='User-Name']")?;
+        let profile_link_selector = parse_selector("a[href^='/']")?;
+        let status_link_selector = parse_selector("a[href*='/status/']")?;
+        let time_selector = parse_selector("time[datetime]")?;
+        let text_selector = parse_selector("div[data-testid='tweetText']")?;
+
+        let tweet_id = card
+            .select(&status_link_selector)
+            .filter_map(|link| link.value().attr("href"))
+            .filter_map(tweet_id_from_href)
+            .find(|id| id != parent_tweet_id);
+
+        let author_handle = card.select(&user_name_selector).next().and_then(|user_name| {
+            user_name
+                .select(&profile_link_selector)
+                .filter_map(|link| link.value().attr("href"))
+                .find_map(handle_from_href)
+        });
+
+        let created_at = card
+            .select(&time_selector)
+            .next()
+            .and_then(|time| time.value().attr("datetime"))
+            .and_then(|datetime| DateTime::parse_from_rfc3339(datetime).ok())
+            .map(|datetime| datetime.with_timezone(&Utc));
+
+        let text = match card.select(&text_selector).next() {
+            Some(container) => TweetText::from_container(container, tweet_id.as_deref())?,
+            None => TweetText::default(),
+        };
+
+        Ok(Self {
+            tweet_id,
+            parent_tweet_id: parent_tweet_id.to_string(),
+            author_handle,
+            text: text.text,
+            created_at,
+            links: urls(&text.links),
+            mentioned_users: text.tags.mentions,
+        })
+    }
+}
+
+/// Returns the quote card of a parsed tweet `article`: the clickable block holding the quoted
+/// tweet's `User-Name`, or `None` if the tweet quotes nothing.
+///
+/// # Errors
+/// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+pub fn quote_card(document: &Html) -> ScrapeResult<Option<ElementRef<'_>>> {
+    let card_selector = parse_selector("div[role='link']")?;
+    let user_name_selector = parse_selector("div[data-testid='User-Name']")?;
+    Ok(document
+        .select(&card_selector)
+        .find(|card| card.select(&user_name_selector).next().is_some()))
+}
+
+/// Returns `true` if `element` lies inside `card`.
+pub fn is_inside(element: &ElementRef, card: Option<ElementRef>) -> bool {
+    card.is_some_and(|card| element.ancestors().any(|ancestor| ancestor.id() == card.id()))
+}
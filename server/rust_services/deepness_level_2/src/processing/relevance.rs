@@ -0,0 +1,173 @@
+//! This module scores processed records for airdrop relevance before they are stored.
+//!
+//! Most tweets of a watched profile have nothing to do with airdrops. `RelevanceScorer` runs
+//! the `[relevance]` rules over each record (keywords in its text, the domains it links to,
+//! its cashtags, addresses and engagement), records which rules matched, a score and a
+//! label, and drops the records scoring below the threshold so they never reach the AI stage.
+
+use crate::config::{RelevanceConfig, RelevanceRule};
+use crate::scraper::user_profile::Airdrop;
+use crate::utils::is_host;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// What a record is about, as far as the rules can tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelevanceLabel {
+    /// A token airdrop, claim or points campaign.
+    Airdrop,
+    /// A testnet, devnet or early access campaign.
+    Testnet,
+    /// A giveaway, raffle or whitelist spot.
+    Giveaway,
+    /// At the threshold through unlabeled rules only, e.g. a token tweet with a contract
+    /// address.
+    Other,
+    /// Nothing the rules recognize, or too little of it.
+    Unrelated,
+}
+
+/// The outcome of scoring a record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Relevance {
+    /// The sum of the weights of the matched rules.
+    pub score: i32,
+    /// The label of the matched rules with the most weight; `other` if no labeled rule added
+    /// weight, `unrelated` below the threshold.
+    pub label: RelevanceLabel,
+    /// Names of the matched rules, in configuration order.
+    pub matched_rules: Vec<String>,
+}
+
+/// Scores records against the configured rules.
+pub struct RelevanceScorer {
+    enabled: bool,
+    filter: bool,
+    min_score: i32,
+    rules: Vec<RelevanceRule>,
+}
+
+impl RelevanceScorer {
+    /// Creates a scorer from the `[relevance]` settings. Keywords and domains are lower-cased
+    /// once here.
+    pub fn new(config: &RelevanceConfig) -> Self {
+        let lowercase = |values: &[String]| values.iter().map(|value| value.trim().to_lowercase()).collect();
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| RelevanceRule {
+                keywords: lowercase(&rule.keywords),
+                domains: lowercase(&rule.domains),
+                ..rule.clone()
+            })
+            .collect();
+        Self {
+            enabled: config.enabled,
+            filter: config.filter,
+            min_score: config.min_score,
+            rules,
+        }
+    }
+
+    /// Scores a single record.
+    pub fn score(&self, airdrop: &Airdrop) -> Relevance {
+        let text = searchable_text(airdrop);
+        let matched: Vec<&RelevanceRule> = self.rules.iter().filter(|rule| matches(rule, airdrop, &text)).collect();
+        let score = matched.iter().map(|rule| rule.weight).sum();
+
+        // The label with the most positive weight wins; on a tie, the first in this order. A
+        // score reached through unlabeled rules alone says nothing about the kind of campaign.
+        let label = if score < self.min_score {
+            RelevanceLabel::Unrelated
+        } else {
+            [RelevanceLabel::Airdrop, RelevanceLabel::Testnet, RelevanceLabel::Giveaway]
+                .into_iter()
+                .map(|label| {
+                    let weight: i32 = matched
+                        .iter()
+                        .filter(|rule| rule.label == Some(label))
+                        .map(|rule| rule.weight.max(0))
+                        .sum();
+                    (label, weight)
+                })
+                .fold((RelevanceLabel::Other, 0), |best, candidate| if candidate.1 > best.1 { candidate } else { best })
+                .0
+        };
+
+        Relevance {
+            score,
+            label,
+            matched_rules: matched.iter().map(|rule| rule.name.clone()).collect(),
+        }
+    }
+
+    /// Scores every record and, with filtering enabled, drops those below the threshold.
+    /// A record is kept when any record of its thread is, so stored threads stay whole.
+    ///
+    /// # Returns
+    /// `usize`: The number of dropped records.
+    pub fn score_airdrops(&self, airdrops: &mut Vec<Airdrop>) -> usize {
+        if !self.enabled {
+            return 0;
+        }
+        for airdrop in airdrops.iter_mut() {
+            airdrop.relevance = Some(self.score(airdrop));
+        }
+        if !self.filter {
+            return 0;
+        }
+
+        let is_relevant = |airdrop: &Airdrop| airdrop.relevance.as_ref().is_some_and(|relevance| relevance.score >= self.min_score);
+        let relevant_threads: HashSet<String> = airdrops
+            .iter()
+            .filter(|airdrop| is_relevant(airdrop))
+            .filter_map(|airdrop| airdrop.threadRootId.clone())
+            .collect();
+        let before = airdrops.len();
+        airdrops.retain(|airdrop| {
+            is_relevant(airdrop) || airdrop.threadRootId.as_ref().is_some_and(|root| relevant_threads.contains(root))
+        });
+        before - airdrops.len()
+    }
+}
+
+/// Returns `true` if the record meets any condition of `rule`.
+fn matches(rule: &RelevanceRule, airdrop: &Airdrop, text: &str) -> bool {
+    let engagement = airdrop.engagement.unwrap_or_default();
+    let at_least = |count: Option<u64>, min: Option<u64>| min.is_some_and(|min| count.is_some_and(|count| count >= min));
+
+    rule.keywords.iter().any(|keyword| contains_word_prefix(text, keyword))
+        || rule.domains.iter().any(|domain| {
+            airdrop
+                .resolvedLinks
+                .iter()
+                .filter_map(|link| link.domain.as_deref())
+                .any(|host| is_host(host, domain))
+        })
+        || (rule.cashtags && !airdrop.cashtags.is_empty())
+        || (rule.addresses && !airdrop.chainAddresses.is_empty())
+        || at_least(engagement.likes, rule.min_likes)
+        || at_least(engagement.reposts, rule.min_reposts)
+}
+
+/// Returns the lower-cased text the keyword rules search: the tweet text, the text it quotes,
+/// the alt texts of its media and its link card, since announcements are often images.
+fn searchable_text(airdrop: &Airdrop) -> String {
+    let quoted = airdrop.quotedTweet.as_ref().and_then(|quoted| quoted.text.as_deref());
+    let media = airdrop.media.iter().flat_map(|media| {
+        let alt_texts = media.items.iter().filter_map(|item| item.alt_text.as_deref());
+        let card = media.card.iter().flat_map(|card| [card.title.as_deref(), card.description.as_deref()]).flatten();
+        alt_texts.chain(card)
+    });
+    airdrop.text.as_deref().into_iter().chain(quoted).chain(media).collect::<Vec<_>>().join("\n").to_lowercase()
+}
+
+/// Returns `true` if `keyword` occurs in `text` at the start of a word.
+fn contains_word_prefix(text: &str, keyword: &str) -> bool {
+    !keyword.is_empty()
+        && text
+            .match_indices(keyword)
+            .any(|(index, _)| text[..index].chars().next_back().is_none_or(|c| !c.is_alphanumeric()))
+}
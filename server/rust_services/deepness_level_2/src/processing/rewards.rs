@@ -0,0 +1,255 @@
+//! This module extracts the reward amounts an airdrop tweet announces ("10,000,000 $XYZ",
+//! "$50k pool", "500 USDC to 10 winners", "earn 1,000 points").
+//!
+//! Amounts are read with their grouping separators and abbreviations (`50k`, `1.5M`, `2
+//! billion`) and typed by what follows or precedes them: a `$` price, a cashtag or known
+//! ticker, the word "tokens", or points. A token amount written with its cashtag is always
+//! kept; dollar, stablecoin and point amounts only in a sentence that talks about a reward
+//! (pool, prize, airdrop, worth, win, ...), so prices and market caps are left out.
+
+use crate::scraper::user_profile::Airdrop;
+use crate::utils::sentences;
+use serde::{Deserialize, Serialize};
+
+/// The unit of a reward amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RewardUnit {
+    /// US dollars, or dollar stablecoins.
+    Usd,
+    /// Tokens; `token` names them when the tweet does.
+    Token,
+    /// Points or XP of a points campaign.
+    Points,
+}
+
+/// A reward amount announced by a tweet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reward {
+    /// The amount, with abbreviations expanded (`50k` is 50000).
+    pub amount: f64,
+    /// What the amount counts.
+    pub unit: RewardUnit,
+    /// The token as a cashtag, e.g. `$JUP`: the token of a token amount, the stablecoin of a
+    /// dollar amount, or the token a dollar amount is paid in ("$50k in $JUP").
+    pub token: Option<String>,
+    /// The expression as written in the tweet, e.g. `$50k pool`.
+    pub expression: String,
+}
+
+/// Tickers recognized after an amount without their `$`, e.g. "5 SOL".
+const KNOWN_TICKERS: [&str; 6] = ["SOL", "ETH", "BTC", "BNB", "USDC", "USDT"];
+
+/// Word prefixes that mark a sentence as talking about a reward.
+const REWARD_WORDS: [&str; 16] = [
+    "reward", "pool", "prize", "airdrop", "allocat", "giveaway", "worth", "win", "distribut", "share", "split", "earn",
+    "bonus", "claim", "bount", "grant",
+];
+
+/// Sets `rewards` on every record from its text.
+pub fn extract_airdrops(airdrops: &mut [Airdrop]) {
+    for airdrop in airdrops.iter_mut() {
+        airdrop.rewards = extract_rewards(airdrop.text.as_deref().unwrap_or_default());
+    }
+}
+
+/// Extracts the reward amounts of a tweet text in order of appearance, without duplicates.
+pub fn extract_rewards(text: &str) -> Vec<Reward> {
+    let mut rewards: Vec<Reward> = Vec::new();
+    for sentence in sentences(text) {
+        let words: Vec<&str> = sentence
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c: char| matches!(c, ',' | '.' | ';' | ':' | '!' | '?' | '(' | ')' | '"' | '+' | '~')))
+            .filter(|word| !word.is_empty())
+            .collect();
+        let lower = sentence.to_lowercase();
+        let about_rewards = lower
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| REWARD_WORDS.iter().any(|prefix| word.starts_with(prefix)));
+
+        let mut index = 0;
+        while index < words.len() {
+            let Some((reward, consumed)) = parse_reward(&words, index) else {
+                index += 1;
+                continue;
+            };
+            let written_with_cashtag = reward.unit == RewardUnit::Token && reward.token.is_some();
+            if (about_rewards || written_with_cashtag) && !rewards.contains(&reward) {
+                rewards.push(reward);
+            }
+            index += consumed;
+        }
+    }
+    rewards
+}
+
+/// Parses a reward starting at `index`.
+///
+/// # Returns
+/// `Option<(Reward, usize)>`: The reward and the number of words consumed.
+fn parse_reward(words: &[&str], index: usize) -> Option<(Reward, usize)> {
+    let word = words[index];
+    let (amount, unit, mut token, mut consumed) = if let Some(price) = word.strip_prefix('$') {
+        // "$50k", "$1.5M", "$2 million".
+        let (amount, multiplier_words) = parse_amount(price, words.get(index + 1).copied())?;
+        (amount, RewardUnit::Usd, None, 1 + multiplier_words)
+    } else {
+        // "10,000,000 $XYZ", "10M JUP tokens", "500 USDC", "1,000 points".
+        let (amount, multiplier_words) = parse_amount(word, words.get(index + 1).copied())?;
+        let unit_index = index + 1 + multiplier_words;
+        let unit_word = words.get(unit_index).copied()?;
+        let after_unit = words.get(unit_index + 1).map(|word| word.to_lowercase());
+        let lower = unit_word.to_lowercase();
+        let (unit, token) = match lower.as_str() {
+            "usd" | "dollars" => (RewardUnit::Usd, None),
+            "usdc" | "usdt" | "$usdc" | "$usdt" => (RewardUnit::Usd, Some(format!("${}", lower.trim_start_matches('$').to_uppercase()))),
+            "points" | "pts" | "xp" => (RewardUnit::Points, None),
+            "tokens" | "token" => (RewardUnit::Token, None),
+            _ => match ticker(unit_word) {
+                Some(ticker) if unit_word.starts_with('$') || KNOWN_TICKERS.contains(&ticker.as_str()) || after_unit.as_deref().is_some_and(|word| word.starts_with("token")) => {
+                    (RewardUnit::Token, Some(format!("${}", ticker)))
+                }
+                _ => return None,
+            },
+        };
+        (amount, unit, token, 1 + multiplier_words + 1)
+    };
+
+    // "$50k in $JUP", "$10,000 worth of $XYZ".
+    if unit == RewardUnit::Usd && token.is_none() {
+        let mut next = index + consumed;
+        if words.get(next).is_some_and(|word| word.eq_ignore_ascii_case("worth")) {
+            next += 1;
+        }
+        if words.get(next).is_some_and(|word| matches!(word.to_lowercase().as_str(), "in" | "of"))
+            && let Some(paid_in) = words.get(next + 1).filter(|word| word.starts_with('$')).and_then(|word| ticker(word))
+        {
+            token = Some(format!("${}", paid_in));
+            consumed = next + 2 - index;
+        }
+    }
+    // Keep the noun that qualifies the amount in the expression: "$50k pool".
+    let qualifier = words
+        .get(index + consumed)
+        .filter(|word| matches!(word.to_lowercase().as_str(), "pool" | "prize" | "prizes" | "rewards" | "reward" | "airdrop" | "allocation"));
+    let expression_end = index + consumed + usize::from(qualifier.is_some());
+
+    Some((
+        Reward {
+            amount,
+            unit,
+            token,
+            expression: words[index..expression_end].join(" "),
+        },
+        consumed,
+    ))
+}
+
+/// Parses an amount such as `10,000,000`, `1.5M`, `50k` or `2` followed by `next` =
+/// "million".
+///
+/// # Returns
+/// `Option<(f64, usize)>`: The amount and whether a multiplier word was consumed (0 or 1).
+fn parse_amount(word: &str, next: Option<&str>) -> Option<(f64, usize)> {
+    let digits_end = word.find(|c: char| !(c.is_ascii_digit() || c == ',' || c == '.')).unwrap_or(word.len());
+    let (number, suffix) = word.split_at(digits_end);
+    if !number.starts_with(|c: char| c.is_ascii_digit()) || !valid_grouping(number) {
+        return None;
+    }
+    let value: f64 = number.replace(',', "").parse().ok()?;
+    let multiplier = |suffix: &str| match suffix.to_lowercase().as_str() {
+        "k" | "thousand" => Some(1e3),
+        "m" | "mm" | "mil" | "million" => Some(1e6),
+        "b" | "bn" | "billion" => Some(1e9),
+        _ => None,
+    };
+    if !suffix.is_empty() {
+        return Some((value * multiplier(suffix)?, 0));
+    }
+    match next.and_then(multiplier) {
+        Some(multiplier) => Some((value * multiplier, 1)),
+        None => Some((value, 0)),
+    }
+}
+
+/// Returns `true` if the commas of `number` group its integer part by thousands.
+fn valid_grouping(number: &str) -> bool {
+    let integer = number.split('.').next().unwrap_or_default();
+    if !integer.contains(',') {
+        return true;
+    }
+    let mut groups = integer.split(',');
+    let first = groups.next().unwrap_or_default();
+    (1..=3).contains(&first.len()) && groups.all(|group| group.len() == 3)
+}
+
+/// Returns the ticker of a cashtag (`$jup`) or upper-case word (`JUP`), upper-cased without
+/// the `$`: two to ten ASCII letters or digits, starting with a letter.
+fn ticker(word: &str) -> Option<String> {
+    let bare = word.strip_prefix('$');
+    let ticker = bare.unwrap_or(word);
+    let valid = (2..=10).contains(&ticker.len())
+        && ticker.starts_with(|c: char| c.is_ascii_alphabetic())
+        && ticker.chars().all(|c| c.is_ascii_alphanumeric())
+        && (bare.is_some() || ticker.chars().all(|c| !c.is_ascii_lowercase()));
+    valid.then(|| ticker.to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_amounts() {
+        let cases = [
+            ("1,000,000", None, Some((1_000_000.0, 0))),
+            ("50k", Some("pool"), Some((50_000.0, 0))),
+            ("1.5M", None, Some((1_500_000.0, 0))),
+            ("2", Some("billion"), Some((2e9, 1))),
+            ("500", Some("USDC"), Some((500.0, 0))),
+            ("1,00,000", None, None),
+            ("10x", None, None),
+            ("k50", None, None),
+            (",000", None, None),
+        ];
+        for (word, next, expected) in cases {
+            assert_eq!(parse_amount(word, next), expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn validates_grouping() {
+        let cases = [
+            ("1,000", true),
+            ("10,000,000", true),
+            ("1,000.50", true),
+            ("1000", true),
+            ("1,00", false),
+            ("1000,000", false),
+            ("1,0000", false),
+        ];
+        for (number, expected) in cases {
+            assert_eq!(valid_grouping(number), expected, "{}", number);
+        }
+    }
+
+    #[test]
+    fn extracts_rewards() {
+        let cases = [
+            ("1,000,000 $XYZ for early users", vec![(1_000_000.0, RewardUnit::Token, Some("$XYZ"), "1,000,000 $XYZ")]),
+            ("A $50k pool for the top 100", vec![(50_000.0, RewardUnit::Usd, None, "$50k pool")]),
+            ("$10,000 worth of $JUP to win", vec![(10_000.0, RewardUnit::Usd, Some("$JUP"), "$10,000 worth of $JUP")]),
+            ("Earn 1,000 points per referral", vec![(1_000.0, RewardUnit::Points, None, "1,000 points")]),
+            ("$SOL at $150 with a $70B market cap", Vec::new()),
+        ];
+        for (text, expected) in cases {
+            let rewards = extract_rewards(text);
+            let found: Vec<_> = rewards
+                .iter()
+                .map(|reward| (reward.amount, reward.unit, reward.token.as_deref(), reward.expression.as_str()))
+                .collect();
+            assert_eq!(found, expected, "{}", text);
+        }
+    }
+}
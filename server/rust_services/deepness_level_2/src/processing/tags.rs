@@ -0,0 +1,211 @@
+//! This module extracts the cheap deterministic signals of a tweet before anything reaches
+//! the LLM: `$TICKER` cashtags, `#hashtags` and `@mentions`, the airdrop vocabulary it uses,
+//! and a token name candidate.
+//!
+//! X renders every cashtag, hashtag and mention in `tweetText` as an anchor
+//! (`/search?q=%24JUP&src=cashtag_click`, `/hashtag/Airdrop`, `/jupiterexchange`), which is
+//! the primary source; the text is scanned as well, for tags X did not link. Tags are
+//! normalized (`$JUP`, `#airdrop`, `@jupiterexchange`) and deduplicated in order of
+//! appearance.
+
+use crate::errors::ScrapeResult;
+use crate::utils::parse_selector;
+use scraper::ElementRef;
+
+/// Word prefixes of the airdrop vocabulary, with the keyword each one is recorded as.
+const AIRDROP_TERMS: [(&str, &str); 18] = [
+    ("airdrop", "airdrop"),
+    ("claim", "claim"),
+    ("snapshot", "snapshot"),
+    ("whitelist", "whitelist"),
+    ("allowlist", "allowlist"),
+    ("testnet", "testnet"),
+    ("mainnet", "mainnet"),
+    ("retroactive", "retroactive"),
+    ("eligib", "eligibility"),
+    ("giveaway", "giveaway"),
+    ("presale", "presale"),
+    ("mint", "mint"),
+    ("points", "points"),
+    ("quest", "quest"),
+    ("reward", "rewards"),
+    ("tge", "tge"),
+    ("stak", "staking"),
+    ("faucet", "faucet"),
+];
+
+/// Tickers that airdrop tweets mostly mention as a price or a requirement ("worth $500 in
+/// $USDC", "stake $SOL") rather than as the token being announced. They are only a token
+/// name candidate when no other cashtag appears.
+const QUOTE_TICKERS: [&str; 9] = ["USD", "USDT", "USDC", "DAI", "BTC", "ETH", "SOL", "BNB", "EUR"];
+
+/// The cashtags, hashtags and mentions of a tweet, normalized and in order of appearance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TweetTags {
+    /// Upper-case cashtags with their `$`, e.g. `$JUP`.
+    pub cashtags: Vec<String>,
+    /// Lower-case hashtags with their `#`, e.g. `#airdrop`.
+    pub hashtags: Vec<String>,
+    /// Lower-case handles with their `@`, e.g. `@jupiterexchange`.
+    pub mentions: Vec<String>,
+}
+
+impl TweetTags {
+    /// Reads the tags of a `div[data-testid='tweetText']` container from its anchors, then
+    /// from its text.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+    pub fn from_container(container: ElementRef) -> ScrapeResult<Self> {
+        let anchor_selector = parse_selector("a[href]")?;
+
+        let mut tags = Self::default();
+        for anchor in container.select(&anchor_selector) {
+            let href = anchor.value().attr("href").unwrap_or_default();
+            let text = anchor.text().collect::<String>();
+            if href.contains("src=cashtag_click") {
+                tags.add_cashtag(&text);
+            } else if href.starts_with("/hashtag/") {
+                tags.add_hashtag(&text);
+            } else if href.starts_with('/') && text.trim().starts_with('@') {
+                tags.add_mention(&text);
+            }
+        }
+        tags.add_from_text(&container.text().collect::<String>());
+        Ok(tags)
+    }
+
+    /// Collects the tags of a tweet from lists of raw values, e.g. the `symbols`, `hashtags`
+    /// and `user_mentions` entities of the GraphQL JSON, then from its text.
+    pub fn from_parts<'a>(
+        cashtags: impl IntoIterator<Item = &'a str>,
+        hashtags: impl IntoIterator<Item = &'a str>,
+        mentions: impl IntoIterator<Item = &'a str>,
+        text: &str,
+    ) -> Self {
+        let mut tags = Self::default();
+        cashtags.into_iter().for_each(|cashtag| tags.add_cashtag(cashtag));
+        hashtags.into_iter().for_each(|hashtag| tags.add_hashtag(hashtag));
+        mentions.into_iter().for_each(|mention| tags.add_mention(mention));
+        tags.add_from_text(text);
+        tags
+    }
+
+    /// Scans `text` for `$`, `#` and `@` tags that start a word. URLs are skipped, so the
+    /// fragment of `https://example.com/#faq` is not read as a hashtag.
+    fn add_from_text(&mut self, text: &str) {
+        for word in text.split_whitespace().filter(|word| !word.contains("://") && !word.starts_with("www.")) {
+            let mut previous: Option<char> = None;
+            for (index, c) in word.char_indices() {
+                let starts_tag = previous.is_none_or(|p| !p.is_alphanumeric() && p != '_');
+                previous = Some(c);
+                if !starts_tag || !matches!(c, '$' | '#' | '@') {
+                    continue;
+                }
+                let tag: String = word[index + 1..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect();
+                match c {
+                    '$' => self.add_cashtag(&tag),
+                    '#' => self.add_hashtag(&tag),
+                    _ => self.add_mention(&tag),
+                }
+            }
+        }
+    }
+
+    fn add_cashtag(&mut self, raw: &str) {
+        push_unique(&mut self.cashtags, normalize_cashtag(raw));
+    }
+
+    fn add_hashtag(&mut self, raw: &str) {
+        push_unique(&mut self.hashtags, normalize_hashtag(raw));
+    }
+
+    fn add_mention(&mut self, raw: &str) {
+        push_unique(&mut self.mentions, normalize_mention(raw));
+    }
+
+    /// Returns the keywords of a tweet: its hashtags without the `#`, its cashtags and the
+    /// airdrop vocabulary found in `text`, without duplicates.
+    pub fn keywords(&self, text: &str) -> Vec<String> {
+        let mut keywords: Vec<String> = Vec::new();
+        for hashtag in &self.hashtags {
+            push_unique(&mut keywords, Some(hashtag.trim_start_matches('#').to_string()));
+        }
+        for cashtag in &self.cashtags {
+            push_unique(&mut keywords, Some(cashtag.clone()));
+        }
+        for word in text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()) {
+            let word = word.to_lowercase();
+            if let Some((_, keyword)) = AIRDROP_TERMS.iter().find(|(prefix, _)| word.starts_with(prefix)) {
+                push_unique(&mut keywords, Some(keyword.to_string()));
+            }
+        }
+        keywords
+    }
+
+    /// Picks the ticker the tweet most likely announces, without its `$`: the most
+    /// frequent cashtag in `text`, the first one on a tie, preferring any cashtag over the
+    /// quote currencies. Without cashtags, an upper-case hashtag such as `#JUP` is used.
+    pub fn token_candidate(&self, text: &str) -> Option<String> {
+        let upper_text = text.to_uppercase();
+        let occurrences = |cashtag: &str| upper_text.matches(cashtag).count();
+        let best = |candidates: Vec<&String>| {
+            candidates
+                .into_iter()
+                .enumerate()
+                .max_by_key(|&(index, cashtag)| (occurrences(cashtag), std::cmp::Reverse(index)))
+                .map(|(_, cashtag)| cashtag.trim_start_matches('$').to_string())
+        };
+        let (quotes, others): (Vec<&String>, Vec<&String>) = self
+            .cashtags
+            .iter()
+            .partition(|cashtag| QUOTE_TICKERS.contains(&cashtag.trim_start_matches('$')));
+        best(others).or_else(|| best(quotes)).or_else(|| {
+            // Hashtags are lower-cased, so look the upper-case spelling up in the text.
+            self.hashtags
+                .iter()
+                .map(|hashtag| hashtag.trim_start_matches('#').to_uppercase())
+                .filter(|ticker| (2..=10).contains(&ticker.len()) && ticker.chars().all(|c| c.is_ascii_alphanumeric()))
+                .filter(|ticker| AIRDROP_TERMS.iter().all(|(prefix, _)| !ticker.to_lowercase().starts_with(prefix)))
+                .find(|ticker| text.contains(&format!("#{}", ticker)))
+        })
+    }
+}
+
+/// Appends `value` unless it is `None` or already present.
+fn push_unique(values: &mut Vec<String>, value: Option<String>) {
+    if let Some(value) = value
+        && !values.contains(&value)
+    {
+        values.push(value);
+    }
+}
+
+/// Normalizes a cashtag to `$TICKER`: one to ten ASCII letters, digits or underscores,
+/// starting with a letter, so prices such as `$500` are rejected.
+fn normalize_cashtag(raw: &str) -> Option<String> {
+    let ticker = raw.trim().trim_start_matches('$');
+    let valid = (1..=10).contains(&ticker.len())
+        && ticker.starts_with(|c: char| c.is_ascii_alphabetic())
+        && ticker.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| format!("${}", ticker.to_uppercase()))
+}
+
+/// Normalizes a hashtag to `#tag`. A hashtag must contain a non-digit, so `#1` is rejected.
+fn normalize_hashtag(raw: &str) -> Option<String> {
+    let tag = raw.trim().trim_start_matches('#');
+    let valid = !tag.is_empty()
+        && tag.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !tag.chars().all(|c| c.is_ascii_digit());
+    valid.then(|| format!("#{}", tag.to_lowercase()))
+}
+
+/// Normalizes a mention to `@handle`: one to fifteen ASCII letters, digits or underscores.
+fn normalize_mention(raw: &str) -> Option<String> {
+    let handle = raw.trim().trim_start_matches('@');
+    let valid = (1..=15).contains(&handle.len()) && handle.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| format!("@{}", handle.to_lowercase()))
+}
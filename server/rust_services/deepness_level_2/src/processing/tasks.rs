@@ -0,0 +1,235 @@
+//! This module extracts the steps an airdrop tweet asks its readers to take ("Follow
+//! @project, like & RT, join our Discord, bridge to Base") as an ordered checklist.
+//!
+//! The text is split into steps at line breaks, list bullets and sentence ends. Each step is
+//! matched against the trigger words of every task kind, and the task's target is taken from
+//! the step itself: the `@handles` to follow, the `$TICKER` to hold or swap, the chain to
+//! bridge to, or the link of the step. Discord, Telegram, quest and form links the text does
+//! not introduce with a step are appended as tasks of their own, so a bare invite link still
+//! makes it into the checklist.
+
+use crate::processing::links::ResolvedLink;
+use crate::processing::tags::TweetTags;
+use crate::scraper::user_profile::Airdrop;
+use crate::utils::{is_host, sentences};
+use serde::{Deserialize, Serialize};
+
+/// What a task asks the reader to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskKind {
+    /// Follow an account; `target` is its `@handle`.
+    Follow,
+    /// Like the tweet, or the tweet `target` links to.
+    Like,
+    /// Repost the tweet, or the tweet `target` links to.
+    Repost,
+    /// Join a Discord server; `target` is the invite link.
+    JoinDiscord,
+    /// Join a Telegram group or channel; `target` is its link.
+    JoinTelegram,
+    /// Complete a Galxe, Zealy, Layer3 or similar quest; `target` is the quest link.
+    CompleteQuest,
+    /// Bridge funds; `target` is the destination chain or the bridge link.
+    Bridge,
+    /// Swap or buy a token; `target` is its `$TICKER` or the swap link.
+    Swap,
+    /// Hold or stake a token; `target` is its `$TICKER`.
+    HoldToken,
+    /// Submit a wallet address; `target` is the form link, if any.
+    SubmitWallet,
+}
+
+/// A step of an airdrop checklist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AirdropTask {
+    /// What to do.
+    pub kind: TaskKind,
+    /// The handle, token, chain or URL the task applies to, `None` if the tweet names none.
+    pub target: Option<String>,
+    /// The text of the step the task was read from, or the link for link-only tasks.
+    pub step: String,
+}
+
+/// Characters that start a new step, besides line breaks and sentence ends.
+const STEP_BULLETS: [char; 10] = ['•', '✅', '☑', '✔', '👉', '➡', '🔸', '🔹', '📌', '🟢'];
+
+/// Quest platform hosts.
+const QUEST_DOMAINS: [&str; 6] = ["galxe.com", "zealy.io", "layer3.xyz", "intract.io", "taskon.xyz", "questn.com"];
+
+/// Form hosts used to collect wallet addresses.
+const FORM_DOMAINS: [&str; 5] = ["docs.google.com", "forms.gle", "typeform.com", "tally.so", "airtable.com"];
+
+/// Chains a bridge step may name, as written in lower case, with the chain.
+const BRIDGE_CHAINS: [(&str, &str); 16] = [
+    ("ethereum", "ethereum"),
+    ("mainnet", "ethereum"),
+    ("solana", "solana"),
+    ("arbitrum", "arbitrum"),
+    ("optimism", "optimism"),
+    ("base", "base"),
+    ("polygon", "polygon"),
+    ("bsc", "bsc"),
+    ("bnb", "bsc"),
+    ("avalanche", "avalanche"),
+    ("linea", "linea"),
+    ("zksync", "zksync"),
+    ("scroll", "scroll"),
+    ("blast", "blast"),
+    ("sui", "sui"),
+    ("aptos", "aptos"),
+];
+
+/// Extracts the checklist of every record from its text and resolved links. Run this after
+/// the shortlinks are resolved, so link targets are the destination URLs.
+pub fn extract_airdrops(airdrops: &mut [Airdrop]) {
+    for airdrop in airdrops.iter_mut() {
+        airdrop.tasks = extract_tasks(airdrop.text.as_deref().unwrap_or_default(), &airdrop.resolvedLinks);
+    }
+}
+
+/// Extracts the tasks of a tweet, in the order its steps appear, without duplicates.
+pub fn extract_tasks(text: &str, links: &[ResolvedLink]) -> Vec<AirdropTask> {
+    let mut tasks: Vec<AirdropTask> = Vec::new();
+    for step in steps(text) {
+        for task in step_tasks(step, links) {
+            push_unique(&mut tasks, task);
+        }
+    }
+
+    // Links no step introduced, e.g. a bare Discord invite at the end of the tweet.
+    for link in links {
+        let url = link.url();
+        let Some(kind) = link.domain.as_deref().and_then(link_kind) else {
+            continue;
+        };
+        if !tasks.iter().any(|task| task.target.as_deref() == Some(url)) {
+            push_unique(
+                &mut tasks,
+                AirdropTask {
+                    kind,
+                    target: Some(url.to_string()),
+                    step: url.to_string(),
+                },
+            );
+        }
+    }
+    tasks
+}
+
+/// Splits the text into steps at line breaks, bullets and sentence ends.
+pub(crate) fn steps(text: &str) -> Vec<&str> {
+    text.split(|c: char| STEP_BULLETS.contains(&c))
+        .flat_map(sentences)
+        .filter(|step| step.chars().any(char::is_alphabetic))
+        .collect()
+}
+
+/// Returns the tasks of a single step.
+fn step_tasks(step: &str, links: &[ResolvedLink]) -> Vec<AirdropTask> {
+    let lower = step.to_lowercase();
+    let words: Vec<&str> = lower.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect();
+    let has_word = |candidates: &[&str]| words.iter().any(|word| candidates.contains(word));
+    let has_prefix = |prefix: &str| words.iter().any(|word| word.starts_with(prefix));
+    let tags = TweetTags::from_parts([], [], [], step);
+    let step_links: Vec<&ResolvedLink> = links.iter().filter(|link| mentions_link(&lower, link)).collect();
+    let link_to = |domains: &[&str]| {
+        step_links
+            .iter()
+            .find(|link| link.domain.as_deref().is_some_and(|domain| domains.iter().any(|known| is_host(domain, known))))
+            .map(|link| link.url().to_string())
+    };
+    let any_link = || step_links.first().map(|link| link.url().to_string());
+
+    let mut tasks = Vec::new();
+    let mut add = |kind: TaskKind, target: Option<String>| {
+        tasks.push(AirdropTask {
+            kind,
+            target,
+            step: step.to_string(),
+        })
+    };
+
+    if has_word(&["follow"]) {
+        if tags.mentions.is_empty() {
+            add(TaskKind::Follow, None);
+        }
+        for mention in &tags.mentions {
+            add(TaskKind::Follow, Some(mention.clone()));
+        }
+    }
+    let reposts = has_word(&["rt", "retweet", "repost"]) || step.contains('🔁') || step.contains('♻');
+    // "Like" is too common a word on its own: it counts next to a repost or as the step's verb.
+    if (has_word(&["like"]) && (reposts || words.first() == Some(&"like"))) || step.contains('❤') {
+        add(TaskKind::Like, link_to(&["x.com", "twitter.com"]));
+    }
+    if reposts {
+        add(TaskKind::Repost, link_to(&["x.com", "twitter.com"]));
+    }
+    if has_word(&["discord"]) {
+        add(TaskKind::JoinDiscord, link_to(&["discord.gg", "discord.com"]));
+    }
+    if has_word(&["telegram", "tg"]) {
+        add(TaskKind::JoinTelegram, link_to(&["t.me", "telegram.me"]));
+    }
+    if has_word(&["galxe", "zealy", "layer3", "intract", "taskon", "quest", "quests"]) {
+        add(TaskKind::CompleteQuest, link_to(&QUEST_DOMAINS).or_else(any_link));
+    }
+    if has_prefix("bridg") {
+        let chain = BRIDGE_CHAINS.iter().find(|(name, _)| has_word(&[*name])).map(|&(_, chain)| chain.to_string());
+        add(TaskKind::Bridge, chain.or_else(any_link));
+    }
+    if has_word(&["swap", "buy", "trade"]) {
+        add(TaskKind::Swap, tags.cashtags.first().cloned().or_else(any_link));
+    }
+    if has_word(&["hold", "holding", "holders", "stake", "staking"]) {
+        add(TaskKind::HoldToken, tags.cashtags.first().cloned());
+    }
+    let asks_for_wallet = has_word(&["wallet", "address", "addresses"])
+        && has_word(&["submit", "drop", "enter", "paste", "comment", "register", "share", "fill"]);
+    if asks_for_wallet || link_to(&FORM_DOMAINS).is_some() {
+        add(TaskKind::SubmitWallet, link_to(&FORM_DOMAINS));
+    }
+    tasks
+}
+
+/// Returns the task a link stands for on its own: a Discord or Telegram invite, a quest or
+/// a form.
+fn link_kind(domain: &str) -> Option<TaskKind> {
+    let is_any = |domains: &[&str]| domains.iter().any(|known| is_host(domain, known));
+    if is_any(&["discord.gg", "discord.com"]) {
+        Some(TaskKind::JoinDiscord)
+    } else if is_any(&["t.me", "telegram.me"]) {
+        Some(TaskKind::JoinTelegram)
+    } else if is_any(&QUEST_DOMAINS) {
+        Some(TaskKind::CompleteQuest)
+    } else if is_any(&FORM_DOMAINS) {
+        Some(TaskKind::SubmitWallet)
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if the lower-cased step shows the link: its shortlink, its URL or, as the
+/// rendered HTML shortens URLs, its domain.
+pub(crate) fn mentions_link(step: &str, link: &ResolvedLink) -> bool {
+    step.contains(&link.short.to_lowercase())
+        || step.contains(&link.url().to_lowercase())
+        || link.domain.as_deref().is_some_and(|domain| step.contains(domain))
+}
+
+/// Appends `task` unless a task of the same kind and target was already found. A target for
+/// an earlier task of the same kind that had none ("Join our Discord 👇" followed by the
+/// invite) completes that task instead.
+fn push_unique(tasks: &mut Vec<AirdropTask>, task: AirdropTask) {
+    let same_kind = |known: &&mut AirdropTask| known.kind == task.kind;
+    if task.target.is_none() && tasks.iter_mut().any(|known| same_kind(&known)) {
+        return;
+    }
+    if let Some(untargeted) = tasks.iter_mut().filter(same_kind).find(|known| known.target.is_none()) {
+        untargeted.target = task.target;
+    } else if !tasks.iter().any(|known| known.kind == task.kind && known.target == task.target) {
+        tasks.push(task);
+    }
+}
@@ -0,0 +1,43 @@
+//! This module links the records of a self-reply thread and joins their text.
+//!
+//! The scraper records each expanded thread in `ScrapedBatch::threads` as the ordered ids of
+//! its tweets. `link_threads` stamps the matching records with `threadRootId` and their
+//! one-based `threadPosition`, and `thread_text` joins a thread back into the single text it
+//! was written as, for analysis that needs the whole announcement.
+
+use crate::scraper::batch::ScrapedBatch;
+use crate::scraper::user_profile::Airdrop;
+
+/// Sets `threadRootId` and `threadPosition` on every record that belongs to one of the
+/// batch's threads. Records outside any thread are left untouched.
+pub fn link_threads(batch: &ScrapedBatch, airdrops: &mut [Airdrop]) {
+    for thread in &batch.threads {
+        for airdrop in airdrops.iter_mut() {
+            if let Some(position) = thread.position_of(&airdrop.tweetId) {
+                airdrop.threadRootId = Some(thread.root_tweet_id.clone());
+                airdrop.threadPosition = Some(position);
+            }
+        }
+    }
+}
+
+/// Joins the text of the thread starting at `root_tweet_id`, in thread order, separated by
+/// blank lines.
+///
+/// # Arguments
+/// * `airdrops` - Records linked by `link_threads`, in any order.
+/// * `root_tweet_id` - The id of the thread's first tweet.
+///
+/// # Returns
+/// `Option<String>`: The joined text, or `None` if no record of the thread has text.
+pub fn thread_text(airdrops: &[Airdrop], root_tweet_id: &str) -> Option<String> {
+    let mut parts: Vec<(u32, &str)> = airdrops
+        .iter()
+        .filter(|airdrop| airdrop.threadRootId.as_deref() == Some(root_tweet_id))
+        .filter_map(|airdrop| Some((airdrop.threadPosition?, airdrop.text.as_deref()?)))
+        .collect();
+    parts.sort_by_key(|&(position, _)| position);
+    parts.dedup_by_key(|&mut (position, _)| position);
+    let text = parts.into_iter().map(|(_, text)| text).collect::<Vec<_>>().join("\n\n");
+    (!text.is_empty()).then_some(text)
+}
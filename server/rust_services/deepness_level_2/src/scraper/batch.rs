@@ -0,0 +1,270 @@
+//! This module defines `ScrapedBatch`, the typed output of a single profile scrape.
+//!
+//! A batch carries the profile it was scraped from, the scrape time window, the tweet HTML
+//! in the order it appeared on the timeline, the self-reply threads expanded from it, the
+//! GraphQL timeline responses captured while scrolling and the statistics of the scrolling
+//! loop, so the processing stage never has to guess which author or order the raw content
+//! belongs to.
+
+use crate::errors::ScrapeResult;
+use crate::scraper::timeline::TimelineEntry;
+use crate::utils::compare_tweet_ids;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{HashSet, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
+};
+
+/// The raw HTML of one tweet `article`, as captured from the timeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TweetHtml {
+    /// Zero-based position of the tweet on the timeline, in the order it was first seen
+    /// while scrolling from the top.
+    pub position: usize,
+    /// When the element's HTML was read from the page.
+    pub captured_at: DateTime<Utc>,
+    /// The outer HTML of the tweet `article`.
+    pub html: String,
+}
+
+/// The JSON body of a GraphQL timeline response the page loaded while scrolling.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphqlResponse {
+    /// The GraphQL operation, e.g. `UserTweets` or `TweetDetail`.
+    pub operation: String,
+    /// The request URL.
+    pub url: String,
+    /// When the response finished loading.
+    pub captured_at: DateTime<Utc>,
+    /// The raw JSON body.
+    pub body: String,
+}
+
+/// A thread of self-replies, collected from the status page of a tweet on the timeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapedThread {
+    /// Id of the tweet starting the thread. Its HTML is in the batch's `tweets`.
+    pub root_tweet_id: String,
+    /// Ids of the thread's tweets in reading order, starting with the root.
+    pub tweet_ids: Vec<String>,
+    /// The HTML of the self-replies, in the order of `tweet_ids` after the root. `position`
+    /// is the reply's place in the thread, the root being at 0.
+    pub replies: Vec<TweetHtml>,
+}
+
+impl ScrapedThread {
+    /// Returns the one-based position of `tweet_id` in the thread, the root being 1.
+    pub fn position_of(&self, tweet_id: &str) -> Option<u32> {
+        let index = self.tweet_ids.iter().position(|id| id == tweet_id)?;
+        u32::try_from(index + 1).ok()
+    }
+}
+
+/// Why the scrolling loop stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// The configured tweet limit was reached.
+    TweetLimit,
+    /// Several consecutive scroll cycles yielded nothing new (end of timeline or stalled loading).
+    NoNewContent,
+    /// The profile shows an empty state and has no posts.
+    EmptyProfile,
+    /// A tweet already collected by an earlier run was reached (see `ScrapeTarget::is_seen`).
+    ReachedWatermark,
+    /// A backfill reached a tweet older than its `until` date.
+    ReachedUntil,
+}
+
+/// A tweet's place on a timeline. The newest one collected is persisted per profile so that
+/// the next run can stop as soon as it reaches this tweet; the oldest one is where an
+/// interrupted backfill resumes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Watermark {
+    /// Id of the newest tweet.
+    pub tweet_id: String,
+    /// Creation time of the newest tweet, when known.
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Statistics about the scrolling loop that produced a batch.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScrapeStats {
+    /// Number of scan-and-scroll cycles performed.
+    pub scroll_cycles: usize,
+    /// Raw number of tweet elements encountered across all cycles, duplicates included.
+    pub elements_seen: usize,
+    /// Number of elements skipped because their HTML had already been collected.
+    pub duplicates_skipped: usize,
+    /// Number of elements whose HTML could not be read.
+    pub read_failures: usize,
+    /// Number of cycles that added no new tweet.
+    pub failed_cycles: usize,
+    /// Why scrolling stopped, once the scrape has finished.
+    pub stop_reason: Option<StopReason>,
+}
+
+/// The typed result of scraping one profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapedBatch {
+    /// The author ID the tweets are attributed to.
+    pub author_id: String,
+    /// The profile URL that was scraped.
+    pub profile_url: String,
+    /// When the scrape started.
+    pub started_at: DateTime<Utc>,
+    /// When the scrape finished. `None` while the scrape is still running.
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Unique tweet HTML entries, ordered by timeline position.
+    pub tweets: Vec<TweetHtml>,
+    /// Threads expanded from tweets of the timeline, see `ScrapingConfig::expand_threads`.
+    #[serde(default)]
+    pub threads: Vec<ScrapedThread>,
+    /// GraphQL timeline responses, in the order they finished loading. Not kept in backfill
+    /// checkpoints, which would grow by hundreds of kilobytes per page; tweets collected
+    /// before a resume are processed from their HTML instead.
+    #[serde(skip)]
+    pub graphql: Vec<GraphqlResponse>,
+    /// Statistics about the scrolling loop.
+    pub stats: ScrapeStats,
+    /// The newest chronological tweet of the batch. Pinned tweets and reposts are ignored,
+    /// since their ids say nothing about how far the timeline has been read.
+    pub watermark: Option<Watermark>,
+    /// The oldest chronological tweet of the batch, under the same rules as `watermark`.
+    pub oldest: Option<Watermark>,
+    /// Hashes of the collected HTML, used to skip elements seen in earlier cycles.
+    #[serde(skip)]
+    seen_hashes: HashSet<u64>,
+}
+
+impl ScrapedBatch {
+    /// Starts an empty batch for the given profile, stamped with the current time.
+    pub fn new(author_id: &str, profile_url: &str) -> Self {
+        Self {
+            author_id: author_id.to_string(),
+            profile_url: profile_url.to_string(),
+            started_at: Utc::now(),
+            finished_at: None,
+            tweets: Vec::new(),
+            threads: Vec::new(),
+            graphql: Vec::new(),
+            stats: ScrapeStats::default(),
+            watermark: None,
+            oldest: None,
+            seen_hashes: HashSet::new(),
+        }
+    }
+
+    /// Appends a tweet's HTML at the next timeline position unless identical HTML was
+    /// already collected.
+    ///
+    /// # Returns
+    /// `bool`: `true` if the HTML was new and has been added, `false` if it was a duplicate.
+    pub fn push_html(&mut self, html: String) -> bool {
+        if !self.seen_hashes.insert(html_hash(&html)) {
+            self.stats.duplicates_skipped += 1;
+            return false;
+        }
+        self.tweets.push(TweetHtml {
+            position: self.tweets.len(),
+            captured_at: Utc::now(),
+            html,
+        });
+        true
+    }
+
+    /// Extends the batch's `watermark` and `oldest` tweet with `entry` if it is a
+    /// chronological tweet newer, respectively older, than them.
+    pub fn observe(&mut self, entry: &TimelineEntry) {
+        let Some(tweet_id) = entry.tweet_id.as_deref() else {
+            return;
+        };
+        if !entry.is_chronological() {
+            return;
+        }
+        let position = Watermark {
+            tweet_id: tweet_id.to_string(),
+            created_at: entry.created_at,
+        };
+        let is_newer = self
+            .watermark
+            .as_ref()
+            .is_none_or(|current| compare_tweet_ids(tweet_id, &current.tweet_id) == Ordering::Greater);
+        if is_newer {
+            self.watermark = Some(position.clone());
+        }
+        let is_older = self
+            .oldest
+            .as_ref()
+            .is_none_or(|current| compare_tweet_ids(tweet_id, &current.tweet_id) == Ordering::Less);
+        if is_older {
+            self.oldest = Some(position);
+        }
+    }
+
+    /// Returns `true` if the scrape stopped at the tweet limit before reaching `previous`, the
+    /// watermark of the earlier run, leaving the tweets between the two unread.
+    pub fn left_gap(&self, previous: Option<&str>) -> bool {
+        self.stats.stop_reason == Some(StopReason::TweetLimit)
+            && previous.is_some_and(|previous| {
+                self.oldest
+                    .as_ref()
+                    .is_none_or(|oldest| compare_tweet_ids(&oldest.tweet_id, previous) == Ordering::Greater)
+            })
+    }
+
+    /// Returns the watermark the next incremental scrape should stop at: the newest tweet of
+    /// the batch, or `None` to keep `previous` when the batch left a gap (see `left_gap`), so
+    /// the next run reads the unread tweets instead of stopping above them.
+    pub fn next_watermark(&self, previous: Option<&str>) -> Option<&Watermark> {
+        if self.left_gap(previous) {
+            return None;
+        }
+        self.watermark.as_ref()
+    }
+
+    /// Rebuilds the duplicate detection state after the batch was deserialized, e.g. from a
+    /// backfill checkpoint.
+    pub fn restore_seen_hashes(&mut self) {
+        self.seen_hashes = self.tweets.iter().map(|tweet| html_hash(&tweet.html)).collect();
+    }
+
+    /// Returns the ids of the collected tweets: the timeline tweets and the tweets of the
+    /// expanded threads.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+    pub fn tweet_ids(&self) -> ScrapeResult<HashSet<String>> {
+        let mut ids = HashSet::new();
+        for tweet in &self.tweets {
+            ids.extend(TimelineEntry::from_html(&tweet.html)?.tweet_id);
+        }
+        ids.extend(self.threads.iter().flat_map(|thread| thread.tweet_ids.iter().cloned()));
+        Ok(ids)
+    }
+
+    /// Returns the number of collected tweets.
+    pub fn len(&self) -> usize {
+        self.tweets.len()
+    }
+
+    /// Returns `true` if no tweet has been collected.
+    pub fn is_empty(&self) -> bool {
+        self.tweets.is_empty()
+    }
+
+    /// Marks the batch as finished, recording the end time and why scrolling stopped.
+    pub fn finish(&mut self, stop_reason: StopReason) {
+        self.finished_at = Some(Utc::now());
+        self.stats.stop_reason = Some(stop_reason);
+    }
+}
+
+/// Hashes a tweet's HTML for duplicate detection.
+fn html_hash(html: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    html.hash(&mut hasher);
+    hasher.finish()
+}
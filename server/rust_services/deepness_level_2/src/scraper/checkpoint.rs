@@ -0,0 +1,79 @@
+//! This module provides `BackfillCheckpoint`, which saves the progress of a backfill to disk
+//! so that a crash or a retry resumes where the previous attempt stopped instead of
+//! scrolling the profile from the top again.
+//!
+//! A checkpoint is the partial `ScrapedBatch` serialized as JSON, one file per profile and
+//! `until` date. It is rewritten while scrolling and removed once the finished batch has been
+//! stored, so a failed store or a crash before it resumes the backfill instead of restarting it.
+
+use crate::errors::ScrapeResult;
+use crate::scraper::batch::ScrapedBatch;
+use chrono::NaiveDate;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The checkpoint file of one profile's backfill.
+#[derive(Debug, Clone)]
+pub struct BackfillCheckpoint {
+    path: PathBuf,
+}
+
+impl BackfillCheckpoint {
+    /// Returns the checkpoint of backfilling `handle` back to `until`, stored in `dir`.
+    pub fn new(dir: &Path, handle: &str, until: NaiveDate) -> Self {
+        let handle: String = handle
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '-' })
+            .collect();
+        Self {
+            path: dir.join(format!("{}-until-{}.json", handle, until)),
+        }
+    }
+
+    /// Loads the partial batch of an interrupted backfill.
+    ///
+    /// # Returns
+    /// `Option<ScrapedBatch>`: `None` if there is no checkpoint or it cannot be read, in which
+    /// case the backfill starts from the top.
+    pub fn load(&self) -> Option<ScrapedBatch> {
+        let contents = fs::read_to_string(&self.path).ok()?;
+        match serde_json::from_str::<ScrapedBatch>(&contents) {
+            Ok(mut batch) => {
+                batch.restore_seen_hashes();
+                println!("Resuming backfill from checkpoint {:?} ({} tweets).", self.path, batch.len());
+                Some(batch)
+            }
+            Err(e) => {
+                println!("Ignoring unreadable backfill checkpoint {:?}: {}", self.path, e);
+                None
+            }
+        }
+    }
+
+    /// Saves the partial batch. The file is written next to the checkpoint and then renamed,
+    /// so a crash while saving never leaves a truncated checkpoint behind.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::Io` if the checkpoint cannot be written.
+    pub fn save(&self, batch: &ScrapedBatch) -> ScrapeResult<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = serde_json::to_string(batch).map_err(std::io::Error::other)?;
+        let temporary = self.path.with_extension("json.tmp");
+        fs::write(&temporary, contents)?;
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+
+    /// Removes the checkpoint once the backfill's batch has been stored, whatever stopped it.
+    pub fn clear(&self) {
+        if let Err(e) = fs::remove_file(&self.path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            println!("Failed to remove backfill checkpoint {:?}: {}", self.path, e);
+        }
+    }
+}
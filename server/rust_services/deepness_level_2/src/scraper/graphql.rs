@@ -0,0 +1,94 @@
+//! This module provides `GraphqlCapture`, which watches a tab's network traffic through the
+//! DevTools protocol and keeps the JSON of the GraphQL timeline requests the page makes
+//! while it scrolls (`UserTweets`, `TweetDetail`, and `SearchTimeline` for the date-bounded
+//! search a resumed backfill scrolls).
+//!
+//! The JSON carries exact ids, timestamps, engagement counts and expanded URLs, and does not
+//! depend on X's DOM. It is processed by `processing::graphql_processor`, with the rendered
+//! HTML as the fallback for tweets no captured response covers.
+
+use crate::scraper::batch::{GraphqlResponse, ScrapedBatch};
+use anyhow::Result;
+use chrono::Utc;
+use headless_chrome::Tab;
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+};
+
+/// Name under which the response handler is registered on the tab.
+const HANDLER_NAME: &str = "graphql_timeline_capture";
+
+/// The GraphQL operations whose responses are kept.
+const CAPTURED_OPERATIONS: [&str; 3] = ["UserTweets", "TweetDetail", "SearchTimeline"];
+
+/// Captures GraphQL timeline responses of a tab until dropped.
+pub struct GraphqlCapture {
+    tab: Arc<Tab>,
+    captured: Arc<Mutex<Vec<GraphqlResponse>>>,
+}
+
+impl GraphqlCapture {
+    /// Enables network events on `tab` and starts capturing. Must be attached before the
+    /// navigation whose responses should be kept.
+    ///
+    /// # Errors
+    /// Returns an error if the network domain cannot be enabled.
+    pub fn attach(tab: &Arc<Tab>) -> Result<Self> {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&captured);
+        tab.register_response_handling(
+            HANDLER_NAME,
+            Box::new(move |params, fetch_body| {
+                let Some(operation) = graphql_operation(&params.response.url) else {
+                    return;
+                };
+                if !CAPTURED_OPERATIONS.contains(&operation) || params.response.status != 200 {
+                    return;
+                }
+                match fetch_body() {
+                    Ok(body) if !body.base_64_encoded => {
+                        let response = GraphqlResponse {
+                            operation: operation.to_string(),
+                            url: params.response.url.clone(),
+                            captured_at: Utc::now(),
+                            body: body.body,
+                        };
+                        sink.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(response);
+                    }
+                    Ok(_) => println!("Warning: Skipping binary {} response.", operation),
+                    Err(e) => println!("Warning: Failed to read the {} response body: {}", operation, e),
+                }
+            }),
+        )?;
+        Ok(Self {
+            tab: Arc::clone(tab),
+            captured,
+        })
+    }
+
+    /// Moves the responses captured so far into `batch`.
+    ///
+    /// # Returns
+    /// `usize`: The number of responses moved.
+    pub fn drain_into(&self, batch: &mut ScrapedBatch) -> usize {
+        let responses = mem::take(&mut *self.captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+        let count = responses.len();
+        batch.graphql.extend(responses);
+        count
+    }
+}
+
+impl Drop for GraphqlCapture {
+    fn drop(&mut self) {
+        let _ = self.tab.deregister_response_handling(HANDLER_NAME);
+    }
+}
+
+/// Returns the operation name of a GraphQL API URL, e.g. `UserTweets` for
+/// `https://x.com/i/api/graphql/<query id>/UserTweets?variables=...`.
+fn graphql_operation(url: &str) -> Option<&str> {
+    let path = url.split('?').next()?;
+    let (_, rest) = path.split_once("/graphql/")?;
+    rest.rsplit('/').next().filter(|operation| !operation.is_empty())
+}
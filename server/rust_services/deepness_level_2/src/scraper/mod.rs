@@ -10,8 +10,26 @@ pub mod tools;
 /// Implements the logic for scraping data from user profiles on a web platform.
 pub mod user_profile;
 
-/// Re-exports the `Scraper` trait for easy access from the parent module.
-pub use traits::Scraper;
+/// Defines `ScrapedBatch`, the typed output of a profile scrape.
+pub mod batch;
+
+/// Reads tweet ids, dates and social context from tweet HTML for the scroll stop conditions.
+pub mod timeline;
+
+/// Saves and restores the progress of interrupted backfills.
+pub mod checkpoint;
+
+/// Captures the GraphQL timeline responses the page loads while scrolling.
+pub mod graphql;
+
+/// Distributes profiles across concurrent `Scraper` workers.
+pub mod pool;
+
+/// Detects thread starts and collects the author's self-replies from a status page.
+pub mod thread;
+
+/// Re-exports the `Scraper` trait and its `ScrapeTarget` for easy access from the parent module.
+pub use traits::{ScrapeTarget, Scraper};
 
 
 /// Re-exports the `UserProfileScraper` struct for easy access from the parent module.
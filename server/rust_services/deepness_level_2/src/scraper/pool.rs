@@ -0,0 +1,609 @@
+//! This module provides the `ScraperPool`, which distributes watchlist profiles across several
+//! `Scraper` workers running concurrently. By default the workers are `UserProfileScraper`s;
+//! `ScraperPool::with_factory` accepts any other `Scraper` implementation.
+//!
+//! Each worker is a dedicated OS thread owning its own scraper, and therefore its own browser
+//! instance and user data directory (and session cookies), because `headless_chrome` is blocking
+//! and a Chrome profile directory cannot be shared between instances. Each worker directory is
+//! seeded with the `manual_cookies.json` of the configured directory, so a saved session works
+//! for every worker. Workers pull jobs from a
+//! shared queue and send every final outcome over a channel, so the async side can process
+//! and persist batches while the other workers keep scraping.
+//!
+//! Failures are isolated per worker and handled according to `ScrapeError::recovery`:
+//! - `Retry`: the profile is requeued after a backoff, up to `pool.max_attempts` attempts. A
+//!   rate limit throttles the session every worker shares, so it also pauses every worker for
+//!   the limit's `retry_after`, or `pool.rate_limit_cooldown_secs`.
+//! - `RotateAccount`: the bot account is blocked or logged out. Every worker uses the same
+//!   account, so there is no other account to rotate to, and relaunching would only hit the
+//!   block again: the pool stops, and the profiles still queued are reported with the error.
+//! - `SkipProfile` and `DropProfile`: the failure is reported immediately.
+//!
+//! A worker whose browser crashed relaunches it; a worker that cannot launch a browser retires
+//! without taking the queue down. When the last worker retires, the remaining jobs are
+//! reported as failed.
+
+use crate::config::{Config, PoolConfig};
+use crate::db::profile_repo::WatchedProfile;
+use crate::errors::{ErrorClass, Recovery, ScrapeError, ScrapeResult};
+use crate::scraper::batch::ScrapedBatch;
+use crate::scraper::{ScrapeTarget, Scraper, UserProfileScraper};
+use std::{
+    collections::VecDeque,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    sync::{Arc, Condvar, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+
+/// The file in a user data directory holding the saved session cookies.
+const COOKIES_FILE: &str = "manual_cookies.json";
+
+/// The final outcome of scraping one profile.
+#[derive(Debug)]
+pub struct PoolOutcome {
+    /// The profile the outcome belongs to.
+    pub profile: WatchedProfile,
+    /// The worker that produced the outcome, `None` if no worker was left to try.
+    pub worker_id: Option<usize>,
+    /// How many attempts were made.
+    pub attempts: u32,
+    /// The scraped batch, or the last error.
+    pub result: ScrapeResult<ScrapedBatch>,
+}
+
+/// Aggregated statistics of a pool run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PoolStats {
+    /// Profiles scraped successfully.
+    pub succeeded: usize,
+    /// Profiles that failed for good.
+    pub failed: usize,
+    /// Attempts that were requeued.
+    pub retried: usize,
+    /// Workers that retired before the queue was empty.
+    pub workers_retired: usize,
+    /// Whether the pool stopped early because the bot account is blocked or logged out.
+    pub halted: bool,
+}
+
+/// A queued profile together with its retry state.
+struct Job {
+    profile: WatchedProfile,
+    attempts: u32,
+    /// The job is not picked before this instant (retry backoff).
+    not_before: Instant,
+    /// The error of the previous attempt, reported if no worker is left to retry.
+    last_error: Option<ScrapeError>,
+}
+
+/// Queue state shared by all workers.
+struct QueueState {
+    jobs: VecDeque<Job>,
+    /// Jobs currently being scraped; they may still be requeued.
+    in_flight: usize,
+    /// Workers that have not retired yet.
+    alive_workers: usize,
+    /// No job is handed out before this instant: the cool-down after a rate limit.
+    paused_until: Instant,
+    /// Set once the bot account failed; no job is handed out anymore.
+    halted: bool,
+    stats: PoolStats,
+}
+
+/// The shared queue: the state and a condition variable to wake idle workers.
+struct SharedQueue {
+    state: Mutex<QueueState>,
+    wakeup: Condvar,
+}
+
+/// Distributes profiles across a fixed number of scraper workers.
+pub struct ScraperPool {
+    queue: Arc<SharedQueue>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ScraperPool {
+    /// Starts `UserProfileScraper` workers and queues the given profiles, in order.
+    ///
+    /// See `with_factory` for the arguments and return value.
+    pub fn start(config: &Config, profiles: Vec<WatchedProfile>) -> (Self, mpsc::UnboundedReceiver<PoolOutcome>) {
+        Self::with_factory(config, profiles, UserProfileScraper::new)
+    }
+
+    /// Starts workers built by `factory` and queues the given profiles, in order.
+    ///
+    /// # Arguments
+    /// * `config` - The application configuration. Each worker gets a copy with its own
+    ///   `browser.user_data_dir` (a `worker-<n>` subdirectory when more than one worker runs,
+    ///   seeded with the saved cookies).
+    /// * `profiles` - The profiles to scrape, highest priority first.
+    /// * `factory` - Builds a worker's scraper from its configuration. It is called on the
+    ///   worker's thread, again whenever the worker relaunches a dead scraper.
+    ///
+    /// # Returns
+    /// The running pool, and the receiving end of the channel on which every profile's final
+    /// `PoolOutcome` is delivered. The channel closes once all workers have exited.
+    pub fn with_factory<S, F>(
+        config: &Config,
+        profiles: Vec<WatchedProfile>,
+        factory: F,
+    ) -> (Self, mpsc::UnboundedReceiver<PoolOutcome>)
+    where
+        S: Scraper,
+        F: Fn(&Config) -> anyhow::Result<S> + Send + Sync + 'static,
+    {
+        let factory = Arc::new(factory);
+        let pool_config = config.pool.clone();
+        let worker_count = pool_config.workers.min(profiles.len()).max(1);
+        let now = Instant::now();
+        let queue = Arc::new(SharedQueue {
+            state: Mutex::new(QueueState {
+                jobs: profiles
+                    .into_iter()
+                    .map(|profile| Job { profile, attempts: 0, not_before: now, last_error: None })
+                    .collect(),
+                in_flight: 0,
+                alive_workers: worker_count,
+                paused_until: now,
+                halted: false,
+                stats: PoolStats::default(),
+            }),
+            wakeup: Condvar::new(),
+        });
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let workers = (0..worker_count)
+            .map(|worker_id| {
+                let mut worker_config = config.clone();
+                if worker_count > 1 {
+                    worker_config.browser.user_data_dir =
+                        config.browser.user_data_dir.join(format!("worker-{}", worker_id));
+                    seed_cookies(&config.browser.user_data_dir, &worker_config.browser.user_data_dir);
+                }
+                let worker = Worker {
+                    id: worker_id,
+                    config: worker_config,
+                    pool: pool_config.clone(),
+                    queue: Arc::clone(&queue),
+                    outcomes: sender.clone(),
+                    factory: Arc::clone(&factory),
+                };
+                thread::Builder::new()
+                    .name(format!("scraper-worker-{}", worker_id))
+                    .spawn(move || worker.run())
+                    .expect("failed to spawn scraper worker thread")
+            })
+            .collect();
+
+        println!("Started a scraper pool with {} workers.", worker_count);
+        (Self { queue, workers }, receiver)
+    }
+
+    /// Waits for every worker to exit and returns the aggregated statistics.
+    pub fn join(self) -> PoolStats {
+        for worker in self.workers {
+            // Worker panics are caught around each scrape; a panic here would be a bug in the
+            // pool itself, and the queue state is still consistent for the remaining workers.
+            let _ = worker.join();
+        }
+        lock(&self.queue.state).stats
+    }
+}
+
+/// One scraper worker: a thread owning a scraper and pulling jobs from the shared queue.
+struct Worker<F> {
+    id: usize,
+    config: Config,
+    pool: PoolConfig,
+    queue: Arc<SharedQueue>,
+    outcomes: mpsc::UnboundedSender<PoolOutcome>,
+    factory: Arc<F>,
+}
+
+/// What a worker does after handling a job.
+enum Next {
+    /// Take the next job with the same browser.
+    Continue,
+    /// The scraper is dead (e.g. its browser crashed); launch a new one before taking the
+    /// next job.
+    Relaunch,
+}
+
+impl<S, F> Worker<F>
+where
+    S: Scraper,
+    F: Fn(&Config) -> anyhow::Result<S>,
+{
+    /// Launches the scraper and processes jobs until the queue is drained or the worker retires.
+    fn run(self) {
+        let Some(mut scraper) = self.launch() else {
+            self.retire();
+            return;
+        };
+
+        while let Some(job) = self.next_job() {
+            match self.scrape(&scraper, job) {
+                Next::Continue => {}
+                Next::Relaunch => {
+                    drop(scraper);
+                    let Some(relaunched) = self.launch() else {
+                        self.retire();
+                        return;
+                    };
+                    scraper = relaunched;
+                }
+            }
+        }
+        self.exit();
+    }
+
+    /// Launches this worker's scraper.
+    fn launch(&self) -> Option<S> {
+        match (self.factory)(&self.config) {
+            Ok(scraper) => Some(scraper),
+            Err(e) => {
+                eprintln!("[worker {}] Failed to launch scraper: {}", self.id, e);
+                None
+            }
+        }
+    }
+
+    /// Blocks until a job is ready for this worker and no rate limit cool-down is running, or
+    /// returns `None` once the queue is empty and no job is in flight anymore, or the pool has
+    /// stopped.
+    fn next_job(&self) -> Option<Job> {
+        let mut state = lock(&self.queue.state);
+        loop {
+            if state.halted {
+                return None;
+            }
+            let now = Instant::now();
+            let paused = state.paused_until.saturating_duration_since(now);
+            let ready = if paused.is_zero() { state.jobs.iter().position(|job| job.not_before <= now) } else { None };
+            if let Some(index) = ready {
+                state.in_flight += 1;
+                return state.jobs.remove(index);
+            }
+            if state.jobs.is_empty() && state.in_flight == 0 {
+                return None;
+            }
+            // Sleep until another worker requeues or finishes a job, or a backoff expires.
+            let wait = state
+                .jobs
+                .iter()
+                .map(|job| job.not_before.saturating_duration_since(now))
+                .filter(|wait| !wait.is_zero())
+                .min()
+                .unwrap_or(Duration::from_secs(1))
+                .max(paused);
+            state = self
+                .queue
+                .wakeup
+                .wait_timeout(state, wait)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+        }
+    }
+
+    /// Scrapes one job and either reports its final outcome or requeues it.
+    fn scrape(&self, scraper: &S, mut job: Job) -> Next {
+        job.attempts += 1;
+        let target = ScrapeTarget {
+            until: self.config.backfill.until_time(),
+            ..job.profile.scrape_target()
+        };
+        println!("[worker {}] Scraping @{} (attempt {}/{})", self.id, job.profile.handle, job.attempts, self.pool.max_attempts);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| scraper.scrape(&target)))
+            .unwrap_or_else(|_| Err(ScrapeError::Browser(format!("worker {} panicked while scraping", self.id))));
+
+        let error = match result {
+            Ok(batch) => {
+                self.finish(job, Ok(batch));
+                return Next::Continue;
+            }
+            Err(error) => error,
+        };
+
+        let recovery = error.recovery();
+        if recovery == Recovery::RotateAccount {
+            self.halt(job, error);
+            return Next::Continue;
+        }
+        let next = if matches!(error, ScrapeError::Browser(_)) && !scraper.is_alive() { Next::Relaunch } else { Next::Continue };
+
+        let mut state = lock(&self.queue.state);
+        let now = Instant::now();
+        if let ScrapeError::RateLimited { retry_after } = &error {
+            let cooldown = retry_after.unwrap_or_else(|| self.pool.rate_limit_cooldown());
+            if now + cooldown > state.paused_until {
+                eprintln!("[worker {}] Rate limited; pausing every worker for {}s.", self.id, cooldown.as_secs());
+                state.paused_until = now + cooldown;
+            }
+        }
+        // A stopped pool hands out no more jobs, so a requeued job would never be reported.
+        let can_retry = !state.halted && error.class() == ErrorClass::Retryable && job.attempts < self.pool.max_attempts;
+        if can_retry {
+            eprintln!("[worker {}] @{} failed ({:?}), requeueing: {}", self.id, job.profile.handle, recovery, error);
+            state.in_flight -= 1;
+            state.stats.retried += 1;
+            job.not_before = (now + self.pool.retry_backoff()).max(state.paused_until);
+            job.last_error = Some(error);
+            state.jobs.push_back(job);
+            self.queue.wakeup.notify_all();
+        } else {
+            drop(state);
+            self.finish(job, Err(error));
+        }
+        next
+    }
+
+    /// Stops the pool after the bot account failed on `job`: no job is handed out anymore, and
+    /// the queued jobs are reported as failed with the same error, since every worker shares
+    /// the account. Jobs other workers are scraping are reported once they finish.
+    fn halt(&self, job: Job, error: ScrapeError) {
+        eprintln!("[worker {}] The bot account failed on @{}, stopping the pool: {}", self.id, job.profile.handle, error);
+        let orphaned: Vec<Job> = {
+            let mut state = lock(&self.queue.state);
+            state.halted = true;
+            state.stats.halted = true;
+            state.stats.failed += state.jobs.len();
+            self.queue.wakeup.notify_all();
+            state.jobs.drain(..).collect()
+        };
+        for orphan in orphaned {
+            let _ = self.outcomes.send(PoolOutcome {
+                profile: orphan.profile,
+                worker_id: None,
+                attempts: orphan.attempts,
+                result: Err(error.copy_account_error()),
+            });
+        }
+        self.finish(job, Err(error));
+    }
+
+    /// Reports a job's final outcome and removes it from the in-flight count.
+    fn finish(&self, job: Job, result: ScrapeResult<ScrapedBatch>) {
+        {
+            let mut state = lock(&self.queue.state);
+            state.in_flight -= 1;
+            match result {
+                Ok(_) => state.stats.succeeded += 1,
+                Err(_) => state.stats.failed += 1,
+            }
+            self.queue.wakeup.notify_all();
+        }
+        let _ = self.outcomes.send(PoolOutcome {
+            profile: job.profile,
+            worker_id: Some(self.id),
+            attempts: job.attempts,
+            result,
+        });
+    }
+
+    /// Retires the worker before the queue is drained. The last worker to leave reports the
+    /// remaining jobs as failed, since nobody is left to scrape them.
+    fn retire(&self) {
+        eprintln!("[worker {}] Retiring.", self.id);
+        let orphaned = {
+            let mut state = lock(&self.queue.state);
+            state.stats.workers_retired += 1;
+            state.alive_workers -= 1;
+            self.queue.wakeup.notify_all();
+            if state.alive_workers == 0 {
+                state.stats.failed += state.jobs.len();
+                state.jobs.drain(..).collect()
+            } else {
+                Vec::new()
+            }
+        };
+        for job in orphaned {
+            let error = job
+                .last_error
+                .unwrap_or_else(|| ScrapeError::Browser("no healthy scraper worker left".to_string()));
+            let _ = self.outcomes.send(PoolOutcome {
+                profile: job.profile,
+                worker_id: None,
+                attempts: job.attempts,
+                result: Err(error),
+            });
+        }
+    }
+
+    /// Leaves the pool after the queue has been drained.
+    fn exit(&self) {
+        let mut state = lock(&self.queue.state);
+        state.alive_workers -= 1;
+        self.queue.wakeup.notify_all();
+    }
+}
+
+/// Copies the saved session cookies of the configured user data directory into a worker's
+/// directory, unless the worker already has cookies at least as recent. Failures only print a
+/// warning: the worker then logs in on its own.
+fn seed_cookies(root_dir: &Path, worker_dir: &Path) {
+    let source = root_dir.join(COOKIES_FILE);
+    let target = worker_dir.join(COOKIES_FILE);
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let Some(source_modified) = modified(&source) else {
+        return;
+    };
+    if modified(&target).is_some_and(|target_modified| target_modified >= source_modified) {
+        return;
+    }
+    if let Err(e) = fs::create_dir_all(worker_dir).and_then(|()| fs::copy(&source, &target)) {
+        println!("Warning: Failed to copy the session cookies to {:?}: {}", target, e);
+    }
+}
+
+/// Locks the queue state, recovering it if a worker panicked while holding the lock.
+fn lock(state: &Mutex<QueueState>) -> std::sync::MutexGuard<'_, QueueState> {
+    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// What a stub scrape of a profile does.
+    enum Step {
+        Succeed,
+        Fail(fn() -> ScrapeError),
+        /// Fails with a browser error and leaves the scraper dead.
+        Crash,
+    }
+
+    /// The scripted steps per handle, shared by every stub a pool launches.
+    #[derive(Default)]
+    struct Script {
+        steps: Mutex<HashMap<String, VecDeque<Step>>>,
+        launches: AtomicUsize,
+        /// Every scrape, in order: the handle and when it started.
+        scrapes: Mutex<Vec<(String, Instant)>>,
+    }
+
+    /// A `Scraper` that plays back the `Script` instead of driving a browser.
+    struct StubScraper {
+        script: Arc<Script>,
+        alive: AtomicBool,
+    }
+
+    impl Scraper for StubScraper {
+        fn scrape(&self, target: &ScrapeTarget) -> ScrapeResult<ScrapedBatch> {
+            self.script.scrapes.lock().unwrap().push((target.handle().to_string(), Instant::now()));
+            let step = self.script.steps.lock().unwrap().get_mut(target.handle()).and_then(VecDeque::pop_front);
+            match step.unwrap_or(Step::Succeed) {
+                Step::Succeed => Ok(ScrapedBatch::new(&target.author_id, &target.profile_url)),
+                Step::Fail(error) => Err(error()),
+                Step::Crash => {
+                    self.alive.store(false, Ordering::SeqCst);
+                    Err(ScrapeError::Browser("connection closed".to_string()))
+                }
+            }
+        }
+
+        fn is_alive(&self) -> bool {
+            self.alive.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Runs a single-worker pool over `handles` with the scripted steps and returns the
+    /// outcomes by handle, the statistics and the script with its record of launches and
+    /// scrapes.
+    fn run(handles: &[&str], steps: Vec<(&str, Vec<Step>)>) -> (HashMap<String, PoolOutcome>, PoolStats, Arc<Script>) {
+        let mut config = Config::default();
+        config.pool.workers = 1;
+        config.pool.max_attempts = 3;
+        config.pool.retry_backoff_secs = 0;
+        let script = Arc::new(Script::default());
+        for (handle, handle_steps) in steps {
+            script.steps.lock().unwrap().insert(handle.to_string(), handle_steps.into());
+        }
+        let profiles = handles
+            .iter()
+            .map(|handle| serde_json::from_value(serde_json::json!({ "handle": handle })).unwrap())
+            .collect();
+
+        let factory_script = Arc::clone(&script);
+        let (pool, mut receiver) = ScraperPool::with_factory(&config, profiles, move |_: &Config| {
+            factory_script.launches.fetch_add(1, Ordering::SeqCst);
+            Ok(StubScraper { script: Arc::clone(&factory_script), alive: AtomicBool::new(true) })
+        });
+        let mut outcomes = HashMap::new();
+        while let Some(outcome) = receiver.blocking_recv() {
+            outcomes.insert(outcome.profile.handle.clone(), outcome);
+        }
+        let stats = pool.join();
+        (outcomes, stats, script)
+    }
+
+    fn selector_not_found() -> ScrapeError {
+        ScrapeError::SelectorNotFound { selector: "article".to_string(), url: "https://x.com/flaky".to_string() }
+    }
+
+    fn profile_not_found() -> ScrapeError {
+        ScrapeError::ProfileNotFound { url: "https://x.com/gone".to_string() }
+    }
+
+    fn rate_limited() -> ScrapeError {
+        ScrapeError::RateLimited { retry_after: Some(Duration::from_millis(200)) }
+    }
+
+    fn login_challenge() -> ScrapeError {
+        ScrapeError::LoginChallenge { challenge: "captcha".to_string() }
+    }
+
+    #[test]
+    fn requeues_a_retryable_failure() {
+        let (outcomes, stats, script) = run(&["flaky"], vec![("flaky", vec![Step::Fail(selector_not_found), Step::Succeed])]);
+        let outcome = &outcomes["flaky"];
+        assert!(outcome.result.is_ok());
+        assert_eq!(outcome.attempts, 2);
+        assert_eq!((stats.succeeded, stats.failed, stats.retried), (1, 0, 1));
+        assert_eq!(script.launches.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn reports_a_retryable_failure_after_the_last_attempt() {
+        let failures = vec![Step::Fail(selector_not_found), Step::Fail(selector_not_found), Step::Fail(selector_not_found)];
+        let (outcomes, stats, _) = run(&["flaky"], vec![("flaky", failures)]);
+        let outcome = &outcomes["flaky"];
+        assert!(matches!(outcome.result, Err(ScrapeError::SelectorNotFound { .. })));
+        assert_eq!(outcome.attempts, 3);
+        assert_eq!((stats.failed, stats.retried), (1, 2));
+    }
+
+    #[test]
+    fn pauses_every_job_after_a_rate_limit() {
+        let (outcomes, stats, script) = run(&["solana", "jup"], vec![("solana", vec![Step::Fail(rate_limited)])]);
+        let scrapes = script.scrapes.lock().unwrap();
+        let (limited, limited_at) = &scrapes[0];
+        assert_eq!(limited, "solana");
+        // The other profile waits for the cool-down too, not only the rate-limited one.
+        assert!(scrapes[1..].iter().all(|(_, at)| at.duration_since(*limited_at) >= Duration::from_millis(200)));
+        assert!(outcomes["solana"].result.is_ok() && outcomes["jup"].result.is_ok());
+        assert_eq!((stats.succeeded, stats.retried), (2, 1));
+    }
+
+    #[test]
+    fn finishes_a_dropped_profile_without_retrying() {
+        let (outcomes, stats, _) = run(&["gone", "solana"], vec![("gone", vec![Step::Fail(profile_not_found)])]);
+        assert!(matches!(outcomes["gone"].result, Err(ScrapeError::ProfileNotFound { .. })));
+        assert_eq!(outcomes["gone"].attempts, 1);
+        assert!(outcomes["solana"].result.is_ok());
+        assert_eq!((stats.succeeded, stats.failed, stats.retried), (1, 1, 0));
+    }
+
+    #[test]
+    fn relaunches_a_dead_scraper() {
+        let (outcomes, stats, script) = run(&["solana", "jup"], vec![("solana", vec![Step::Crash])]);
+        assert!(outcomes["solana"].result.is_ok());
+        assert_eq!(outcomes["solana"].attempts, 2);
+        assert!(outcomes["jup"].result.is_ok());
+        assert_eq!(script.launches.load(Ordering::SeqCst), 2);
+        assert_eq!(stats.workers_retired, 0);
+    }
+
+    #[test]
+    fn stops_the_pool_when_the_account_fails() {
+        let (outcomes, stats, script) = run(&["solana", "jup", "bonk"], vec![("solana", vec![Step::Fail(login_challenge)])]);
+        assert_eq!(outcomes.len(), 3);
+        assert!(matches!(outcomes["solana"].result, Err(ScrapeError::LoginChallenge { .. })));
+        assert_eq!(outcomes["solana"].worker_id, Some(0));
+        for handle in ["jup", "bonk"] {
+            assert!(matches!(outcomes[handle].result, Err(ScrapeError::LoginChallenge { .. })));
+            assert_eq!((outcomes[handle].worker_id, outcomes[handle].attempts), (None, 0));
+        }
+        assert!(stats.halted);
+        assert_eq!((stats.succeeded, stats.failed, stats.retried), (0, 3, 0));
+        // The blocked account is not relaunched, and nothing else is scraped with it.
+        assert_eq!(script.launches.load(Ordering::SeqCst), 1);
+        assert_eq!(script.scrapes.lock().unwrap().len(), 1);
+    }
+}
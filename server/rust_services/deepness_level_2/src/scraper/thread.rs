@@ -0,0 +1,152 @@
+//! This module reconstructs self-reply threads. Airdrop announcements are often posted as a
+//! numbered thread ("1/7", "2/7", ...) of which the profile timeline only shows the first
+//! tweet.
+//!
+//! `looks_like_thread_start` picks the timeline tweets worth expanding, and
+//! `ThreadCollector` reads the articles of a tweet's status page in order, keeping the
+//! author's self-replies that directly follow the tweet. Opening the status page and
+//! scrolling it is left to `UserProfileScraper`.
+
+use crate::errors::ScrapeResult;
+use crate::processing::classification::TweetRelations;
+use crate::processing::quoted_tweet::{is_inside, quote_card};
+use crate::scraper::batch::{ScrapedThread, TweetHtml};
+use crate::scraper::timeline::TimelineEntry;
+use crate::utils::parse_selector;
+use chrono::Utc;
+use scraper::Html;
+
+/// Returns `true` if a parsed tweet `article` looks like the start of a thread: its text is
+/// numbered as the first of several tweets ("1/7", "1/n", "(1/"), carries the 🧵 emoji or the
+/// word "thread", or the timeline shows "Show this thread" under it.
+///
+/// # Errors
+/// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+pub fn looks_like_thread_start(document: &Html) -> ScrapeResult<bool> {
+    let text_selector = parse_selector("div[data-testid='tweetText']")?;
+    let link_selector = parse_selector("a, span")?;
+
+    let card = quote_card(document)?;
+    let text: String = document
+        .select(&text_selector)
+        .find(|el| !is_inside(el, card))
+        .map(|el| el.text().collect())
+        .unwrap_or_default();
+    let announces_thread = text.contains('🧵')
+        || text
+            .split_whitespace()
+            .any(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).eq_ignore_ascii_case("thread"));
+    let shows_thread_link = document
+        .select(&link_selector)
+        .any(|el| el.text().collect::<String>().trim() == "Show this thread");
+
+    Ok(has_first_of_numbering(&text) || announces_thread || shows_thread_link)
+}
+
+/// Returns `true` if `text` contains "1/" numbering the first tweet of several: "1/7",
+/// "1/n", "(1/10)" or a bare "1/". Dates such as "1/2/2025" and "11/" do not count.
+fn has_first_of_numbering(text: &str) -> bool {
+    text.match_indices("1/").any(|(index, _)| {
+        let boundary_before = text[..index]
+            .chars()
+            .next_back()
+            .is_none_or(|c| c.is_whitespace() || c == '(' || c == '[');
+        let after = &text[index + 2..];
+        let count: String = after.chars().take_while(char::is_ascii_alphanumeric).collect();
+        let rest = &after[count.len()..];
+        let is_count = match count.as_str() {
+            "" => rest.chars().next().is_none_or(|c| !c.is_ascii_punctuation() || c == ')' || c == ']'),
+            "n" | "N" => true,
+            digits => digits.chars().all(|c| c.is_ascii_digit()) && digits != "1",
+        };
+        boundary_before && is_count && !rest.starts_with('/')
+    })
+}
+
+/// Collects the self-replies that follow a tweet on its status page.
+///
+/// Articles must be passed in page order, scroll after scroll; already seen ones may be
+/// passed again. Articles above the root (the tweets it replies to) are ignored; the thread
+/// ends at the first article below the root written by another account.
+#[derive(Debug)]
+pub struct ThreadCollector {
+    handle: String,
+    max_length: usize,
+    root_seen: bool,
+    finished: bool,
+    thread: ScrapedThread,
+}
+
+impl ThreadCollector {
+    /// Starts collecting the thread of `handle` that begins with `root_tweet_id`, up to
+    /// `max_length` self-replies.
+    pub fn new(handle: &str, root_tweet_id: &str, max_length: usize) -> Self {
+        Self {
+            handle: handle.to_string(),
+            max_length,
+            root_seen: false,
+            finished: false,
+            thread: ScrapedThread {
+                root_tweet_id: root_tweet_id.to_string(),
+                tweet_ids: vec![root_tweet_id.to_string()],
+                replies: Vec::new(),
+            },
+        }
+    }
+
+    /// Reads the next article of the status page.
+    ///
+    /// # Returns
+    /// `ScrapeResult<bool>`: `true` if the article was a new self-reply and was added.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+    pub fn observe(&mut self, html: String) -> ScrapeResult<bool> {
+        if self.finished {
+            return Ok(false);
+        }
+        let document = Html::parse_fragment(&html);
+        let entry = TimelineEntry::from_document(&document)?;
+        let Some(tweet_id) = entry.tweet_id.clone() else {
+            return Ok(false);
+        };
+        if tweet_id == self.thread.root_tweet_id {
+            self.root_seen = true;
+            return Ok(false);
+        }
+        if !self.root_seen || self.thread.tweet_ids.contains(&tweet_id) {
+            return Ok(false);
+        }
+
+        let relations = TweetRelations::from_document(&document, &entry)?;
+        let is_self_reply = relations
+            .author_handle
+            .as_deref()
+            .is_some_and(|author| author.eq_ignore_ascii_case(&self.handle));
+        if !is_self_reply {
+            self.finished = true;
+            return Ok(false);
+        }
+
+        self.thread.replies.push(TweetHtml {
+            position: self.thread.tweet_ids.len(),
+            captured_at: Utc::now(),
+            html,
+        });
+        self.thread.tweet_ids.push(tweet_id);
+        if self.thread.replies.len() >= self.max_length {
+            self.finished = true;
+        }
+        Ok(true)
+    }
+
+    /// Returns `true` once the thread has ended or reached its maximum length.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the collected thread.
+    pub fn into_thread(self) -> ScrapedThread {
+        self.thread
+    }
+}
@@ -0,0 +1,90 @@
+//! This module reads the few facts the scrolling loop needs from a tweet's HTML while the
+//! tweet is still on the page: its id, its creation time and whether the timeline shows it
+//! out of chronological order (a pinned tweet or a repost).
+//!
+//! The processing stage reuses the same reader, so the scraper's stop conditions and the
+//! stored records always agree on a tweet's id and date.
+
+use crate::errors::ScrapeResult;
+use crate::utils::{parse_selector, tweet_id_from_href};
+use chrono::{DateTime, Utc};
+use scraper::{ElementRef, Html};
+
+/// What the scrolling loop knows about a tweet on the timeline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimelineEntry {
+    /// The tweet id, taken from the tweet's permalink.
+    pub tweet_id: Option<String>,
+    /// When the tweet was posted, taken from its `<time datetime>` element.
+    pub created_at: Option<DateTime<Utc>>,
+    /// The social context line shown above the tweet, e.g. "Pinned" or "Alice reposted".
+    pub social_context: Option<String>,
+}
+
+impl TimelineEntry {
+    /// Reads an entry from the outer HTML of a tweet `article`.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+    pub fn from_html(html: &str) -> ScrapeResult<Self> {
+        Self::from_document(&Html::parse_fragment(html))
+    }
+
+    /// Reads an entry from an already parsed tweet `article`.
+    ///
+    /// The permalink wrapping the first `<time>` element is preferred, since it belongs to the
+    /// tweet itself rather than to a quoted tweet; other status links are the fallback.
+    ///
+    /// # Errors
+    /// Returns `ScrapeError::InvalidSelector` if a CSS selector cannot be parsed.
+    pub fn from_document(document: &Html) -> ScrapeResult<Self> {
+        let time_selector = parse_selector("time[datetime]")?;
+        let permalink_selector = parse_selector("a[href*='/status/']")?;
+        let social_context_selector = parse_selector("[data-testid='socialContext']")?;
+
+        let mut entry = Self::default();
+
+        // Attempt to extract tweet ID and creation time from the <time> element and its parent.
+        if let Some(time_element) = document.select(&time_selector).next() {
+            entry.created_at = time_element
+                .value()
+                .attr("datetime")
+                .and_then(|datetime| DateTime::parse_from_rfc3339(datetime).ok())
+                .map(|datetime| datetime.with_timezone(&Utc));
+
+            // Sometimes the permalink is the parent <a> of the <time> element
+            if let Some(parent_link_element) = time_element.parent().and_then(ElementRef::wrap)
+                && parent_link_element.value().name() == "a"
+                && let Some(href) = parent_link_element.value().attr("href")
+            {
+                entry.tweet_id = tweet_id_from_href(href);
+            }
+        }
+
+        // If tweet ID was not found via the time element's parent, try other permalinks,
+        // skipping links that only open a photo or video of the tweet.
+        if entry.tweet_id.is_none() {
+            entry.tweet_id = document
+                .select(&permalink_selector)
+                .filter_map(|link| link.value().attr("href"))
+                .filter(|href| !href.contains("/photo/") && !href.contains("/video/"))
+                .find_map(tweet_id_from_href);
+        }
+
+        entry.social_context = document
+            .select(&social_context_selector)
+            .next()
+            .map(|element| element.text().collect::<String>().trim().to_string())
+            .filter(|text| !text.is_empty());
+
+        Ok(entry)
+    }
+
+    /// Returns `true` if the tweet sits at its chronological place on the timeline.
+    ///
+    /// Pinned tweets and reposts carry a social context and can be much older than the tweets
+    /// around them, so their dates must not end a scroll.
+    pub fn is_chronological(&self) -> bool {
+        self.social_context.is_none()
+    }
+}
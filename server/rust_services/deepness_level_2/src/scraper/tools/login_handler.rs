@@ -3,20 +3,25 @@
 //! It includes logic for filling forms, clicking buttons, and
 //! handling session persistence via cookies.
 
-use anyhow::{anyhow, Result};
 use headless_chrome::{
     Tab,
-    protocol::cdp::{Page, Network::{CookieParam, TimeSinceEpoch}},
+    protocol::cdp::Page,
 };
 use std::{
-    fs::{File},
-    io::{Write},
-    path::PathBuf,
+    fs::File,
+    io::Write,
+    path::Path,
     sync::Arc,
     thread,
     time::{Duration, Instant},
 };
 
+use crate::config::LoginConfig;
+use crate::errors::{ScrapeError, ScrapeResult};
+
+/// Selector matching the CAPTCHA iframe X shows when it challenges a login.
+const CHALLENGE_SELECTOR: &str = "iframe#arkose_iframe, iframe[src*='arkoselabs']";
+
 /// `LoginHandler` provides static methods to handle login flows
 /// and cookie management within a `headless_chrome` session.
 pub struct LoginHandler;
@@ -38,14 +43,18 @@ impl LoginHandler {
     /// * `final_login_button_selector_in_form` - CSS selector for the final login submission button.
     /// * `success_indicator_url_part` - An optional string part that should appear in the URL upon successful login.
     /// * `success_indicator_selector` - An optional CSS selector for an element that indicates successful login.
+    /// * `credentials` - The login section of the configuration holding the bot account credentials.
     /// * `user_data_dir` - The path to the user data directory where cookies should be saved.
     ///
     /// # Returns
-    /// `Result<()>`: `Ok(())` if login appears successful, otherwise an `anyhow::Error`.
+    /// `ScrapeResult<()>`: `Ok(())` if login appears successful, otherwise a `ScrapeError`.
     ///
     /// # Errors
-    /// Returns an error if elements are not found, interactions fail, or login
-    /// cannot be confirmed within the timeout.
+    /// * `ScrapeError::AccountSuspended` if the platform redirects to an account lock page.
+    /// * `ScrapeError::LoginChallenge` if a CAPTCHA or verification challenge blocks the flow.
+    /// * `ScrapeError::LoginFailed` if login cannot be confirmed within the timeout.
+    /// * `ScrapeError::Browser` if elements are not found or interactions fail.
+    #[allow(clippy::too_many_arguments)]
     pub fn attempt_login(
         tab: &Arc<Tab>,
         initial_login_trigger_selector: &str,
@@ -55,15 +64,15 @@ impl LoginHandler {
         final_login_button_selector_in_form: &str,
         success_indicator_url_part: Option<&str>,
         success_indicator_selector: Option<&str>,
-        user_data_dir: &PathBuf, // Added to save cookies
-    ) -> Result<()> {
-        // Placeholder credentials - IMPORTANT: Replace with actual secure credentials
-        let username = "@bot_username"; 
-        let password = "bot_password"; 
-        let email = "bot_temp_email"; 
+        credentials: &LoginConfig,
+        user_data_dir: &Path, // Added to save cookies
+    ) -> ScrapeResult<()> {
+        let username = credentials.username.as_str();
+        let password = credentials.password.as_str();
+        let email = credentials.email.as_str();
 
-        if username == "YOUR_USERNAME_HERE" || password == "YOUR_PASSWORD_HERE" || email == "YOUR_EMAIL_HERE"{
-            println!("WARNING: Using placeholder credentials in attempt_login. Please update them with real ones if login is intended.");
+        if username.is_empty() || password.is_empty() {
+            println!("WARNING: No login credentials configured. Set DEEPNESS_LOGIN_USERNAME and DEEPNESS_LOGIN_PASSWORD if login is intended.");
         }
 
         println!("Looking for initial login trigger button on current page: {}", initial_login_trigger_selector);
@@ -76,11 +85,11 @@ impl LoginHandler {
                 // If the trigger isn't found, check if we're already on a login form or logged in.
                 if tab.find_element(username_selector_in_form).is_err() {
                     println!("Initial login trigger not found, and username field also not immediately visible. Potentially already logged in or on an unexpected page.");
-                    if let Some(selector) = success_indicator_selector {
-                        if tab.find_element(selector).is_ok() {
-                            println!("Login success indicator found. Assuming already logged in.");
-                            return Ok(());
-                        }
+                    if let Some(selector) = success_indicator_selector
+                        && tab.find_element(selector).is_ok()
+                    {
+                        println!("Login success indicator found. Assuming already logged in.");
+                        return Ok(());
                     }
                     if let Some(url_part) = success_indicator_url_part {
                         let current_url_direct: String = tab.get_url();
@@ -152,11 +161,11 @@ impl LoginHandler {
             }
             Err(_) => {
                 println!("Password input field not found. Assuming login is already complete or a different flow occurred.");
-                if let Some(selector) = success_indicator_selector {
-                    if tab.find_element(selector).is_ok() {
-                        println!("Login success indicator found after attempting password step. Assuming already logged in.");
-                        return Ok(());
-                    }
+                if let Some(selector) = success_indicator_selector
+                    && tab.find_element(selector).is_ok()
+                {
+                    println!("Login success indicator found after attempting password step. Assuming already logged in.");
+                    return Ok(());
                 }
                 if let Some(url_part) = success_indicator_url_part {
                     let current_url_direct: String = tab.get_url();
@@ -183,12 +192,12 @@ impl LoginHandler {
                 }
             }
 
-            if let Some(selector) = success_indicator_selector {
-                if tab.find_element(selector).is_ok() {
-                    println!("Login success indicated by element: {}", selector);
-                    login_successful = true;
-                    break;
-                }
+            if let Some(selector) = success_indicator_selector
+                && tab.find_element(selector).is_ok()
+            {
+                println!("Login success indicated by element: {}", selector);
+                login_successful = true;
+                break;
             }
             thread::sleep(Duration::from_secs(1));
         }
@@ -250,7 +259,14 @@ impl LoginHandler {
                     println!("Failed to save login_failure.png");
                 }
             }
-            Err(anyhow!("Login failed or could not be confirmed within timeout. Check for CAPTCHAs, 2FA, incorrect credentials/selectors, or the phone/email verification step. Current URL: {}", tab.get_url()))
+            let current_url = tab.get_url();
+            if current_url.contains("/account/access") || current_url.contains("/account/suspended") {
+                return Err(ScrapeError::AccountSuspended { url: current_url });
+            }
+            if tab.find_element(CHALLENGE_SELECTOR).is_ok() {
+                return Err(ScrapeError::LoginChallenge { challenge: format!("CAPTCHA or verification challenge at {}", current_url) });
+            }
+            Err(ScrapeError::LoginFailed { reason: format!("could not be confirmed within timeout. Check for 2FA, incorrect credentials/selectors, or the phone/email verification step. Current URL: {}", current_url) })
         }
     }
 }
\ No newline at end of file
@@ -1,23 +1,96 @@
 //! This module defines traits that establish common interfaces for different web scrapers.
 
-use anyhow::Result;
+use crate::errors::ScrapeResult;
+use crate::scraper::batch::ScrapedBatch;
+use crate::scraper::timeline::TimelineEntry;
+use crate::utils::{compare_tweet_ids, handle_from_url};
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
 
-/// `Scraper` is an asynchronous trait that defines the core behavior for any web scraper.
+/// Identifies what a `Scraper` should collect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrapeTarget {
+    /// The URL of the page to scrape, e.g. a profile URL.
+    pub profile_url: String,
+    /// The author id attributed to the scraped tweets.
+    pub author_id: String,
+    /// Incremental scrapes stop at this tweet or any older one.
+    pub since_tweet_id: Option<String>,
+    /// Incremental scrapes stop at a tweet posted at or before this time. Only used for
+    /// tweets whose id cannot be compared with `since_tweet_id`.
+    pub since_time: Option<DateTime<Utc>>,
+    /// Backfill mode: scroll past the tweet limit and the watermark until a tweet posted
+    /// before this time is reached.
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl ScrapeTarget {
+    /// Creates a target for the given page and author.
+    pub fn new(profile_url: impl Into<String>, author_id: impl Into<String>) -> Self {
+        Self {
+            profile_url: profile_url.into(),
+            author_id: author_id.into(),
+            since_tweet_id: None,
+            since_time: None,
+            until: None,
+        }
+    }
+
+    /// Returns the handle of the profile, i.e. the last segment of `profile_url`.
+    pub fn handle(&self) -> &str {
+        handle_from_url(&self.profile_url)
+    }
+
+    /// Returns `true` if `entry` was already collected by an earlier run, i.e. it is at or
+    /// before the target's watermark.
+    ///
+    /// Only chronological tweets are judged: a pinned tweet or a repost is never "seen",
+    /// whatever its id or date.
+    pub fn is_seen(&self, entry: &TimelineEntry) -> bool {
+        if !entry.is_chronological() {
+            return false;
+        }
+        if let (Some(tweet_id), Some(since)) = (&entry.tweet_id, &self.since_tweet_id) {
+            return compare_tweet_ids(tweet_id, since) != Ordering::Greater;
+        }
+        if let (Some(created_at), Some(since)) = (entry.created_at, self.since_time) {
+            return created_at <= since;
+        }
+        false
+    }
+
+    /// Returns `true` if `entry` is a chronological tweet posted before the backfill's `until`
+    /// time. Pinned tweets and reposts never end a backfill, however old they are.
+    pub fn is_past_until(&self, entry: &TimelineEntry) -> bool {
+        match (self.until, entry.created_at) {
+            (Some(until), Some(created_at)) => entry.is_chronological() && created_at < until,
+            _ => false,
+        }
+    }
+}
+
+/// `Scraper` is the trait that defines the core behavior for any web scraper.
 ///
-/// Implementors of this trait are expected to perform a scraping operation
-/// based on a given identifier.
-#[async_trait::async_trait]
+/// Implementors collect the tweets of a `ScrapeTarget` into a `ScrapedBatch`. The trait is
+/// synchronous because the browser API is blocking: orchestration code such as the
+/// `ScraperPool` runs each scraper on its own thread and only depends on this trait, so it
+/// can drive a mock implementation just as well as a real browser.
 pub trait Scraper {
-    /// Asynchronously scrapes data using the provided identifier.
-    ///
-    /// The `identifier` could be a URL, a user ID, a search query, or any
-    /// other string that helps the scraper locate and retrieve the desired information.
+    /// Scrapes the given target.
     ///
     /// # Arguments
-    /// * `identifier` - A string slice that uniquely identifies the target for scraping.
+    /// * `target` - What to scrape.
     ///
     /// # Returns
-    /// `Result<()>`: An `Ok(())` on successful completion of the scraping operation,
-    /// or an `anyhow::Error` if an error occurs during scraping.
-    async fn scrape(&self, identifier: &str) -> Result<()>;
-}
\ No newline at end of file
+    /// `ScrapeResult<ScrapedBatch>`: The collected tweets in timeline order, or a
+    /// `ScrapeError` whose `recovery()` tells the caller how to proceed.
+    fn scrape(&self, target: &ScrapeTarget) -> ScrapeResult<ScrapedBatch>;
+
+    /// Returns `true` if the scraper can still be used after a failed scrape.
+    ///
+    /// Used by the scraper pool to tell a failed page interaction from a crashed browser.
+    /// Scrapers without external resources are always alive.
+    fn is_alive(&self) -> bool {
+        true
+    }
+}
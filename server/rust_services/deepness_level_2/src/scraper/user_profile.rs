@@ -2,28 +2,52 @@
 //! navigating to user profiles on a social media platform and
 //! extracting tweet HTML content by dynamically scrolling.
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use chrono::{DateTime, Utc};
 use headless_chrome::{
     Browser, Tab,
+    browser::tab::NoElementFound,
     protocol::cdp::Page,
 };
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Ordering,
     collections::HashSet,
     fs::{self, File},
-    io::{Read, Write},
+    io::Read,
     path::PathBuf,
     sync::Arc,
     thread,
-    time::{Duration, Instant},
 };
 
+use crate::config::{BackfillConfig, Config, LoginConfig, ScrapingConfig};
+use crate::errors::{ScrapeError, ScrapeResult};
+use crate::processing::addresses::ChainAddress;
+use crate::processing::chains::ChainMatch;
+use crate::processing::classification::TweetKind;
+use crate::processing::deadlines::Deadline;
+use crate::processing::engagement::Engagement;
+use crate::processing::link_safety::RiskFlag;
+use crate::processing::links::ResolvedLink;
+use crate::processing::media::Media;
+use crate::processing::quoted_tweet::QuotedTweet;
+use crate::processing::relevance::Relevance;
+use crate::processing::rewards::Reward;
+use crate::processing::tasks::AirdropTask;
+use crate::scraper::batch::{ScrapedBatch, StopReason, Watermark};
+use crate::scraper::checkpoint::BackfillCheckpoint;
+use crate::scraper::graphql::GraphqlCapture;
+use crate::scraper::thread::{ThreadCollector, looks_like_thread_start};
+use crate::scraper::timeline::TimelineEntry;
 // Import the new LoginHandler module
 use crate::scraper::tools::login_handler::LoginHandler;
+use crate::scraper::traits::{ScrapeTarget, Scraper};
+use crate::utils::compare_tweet_ids;
 
 /// Represents an Airdrop event, typically a tweet containing
 /// information about a cryptocurrency airdrop.
+/// Field names are kept in camelCase to match the `AirdropSchema` used by the Express server.
+#[allow(non_snake_case)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Airdrop {
     pub tweetId: String,
@@ -35,7 +59,54 @@ pub struct Airdrop {
     pub keywords: Vec<String>,
     pub tokenName: Option<String>,
     pub mentionedUsers: Vec<String>,
+    /// The `$TICKER` cashtags of the tweet, upper-cased.
+    pub cashtags: Vec<String>,
+    /// The `#hashtags` of the tweet, lower-cased.
+    pub hashtags: Vec<String>,
+    /// The external links of the tweet, expanded where known (see `resolvedLinks`).
     pub links: Vec<String>,
+    /// Each external link as its shortlink, expanded URL and domain.
+    pub resolvedLinks: Vec<ResolvedLink>,
+    /// Engagement counts at scrape time, `None` if the tweet showed none.
+    pub engagement: Option<Engagement>,
+    /// What the timeline item is: an original tweet, a repost, a reply, a quote or the pinned tweet.
+    pub tweetType: Option<TweetKind>,
+    /// Handle of the account that wrote the tweet. For reposts this is not the scraped profile.
+    pub authorHandle: Option<String>,
+    /// Handle of the account that reposted the tweet, for reposts.
+    pub repostedBy: Option<String>,
+    /// Id of the tweet this one replies to.
+    pub inReplyToTweetId: Option<String>,
+    /// Handle of the account this tweet replies to.
+    pub inReplyToHandle: Option<String>,
+    /// Id of the tweet this one quotes.
+    pub quotedTweetId: Option<String>,
+    /// The tweet this one quotes, as a nested record.
+    pub quotedTweet: Option<QuotedTweet>,
+    /// Id of the first tweet of the self-reply thread this tweet belongs to.
+    pub threadRootId: Option<String>,
+    /// One-based position of the tweet in its thread, the root being 1.
+    pub threadPosition: Option<u32>,
+    /// Attached images, videos and GIFs and the link preview card, `None` if there are none.
+    pub media: Option<Media>,
+    /// Blockchain addresses and names found in the text and the expanded links.
+    pub chainAddresses: Vec<ChainAddress>,
+    /// The rule-based relevance score and label, `None` while unscored.
+    pub relevance: Option<Relevance>,
+    /// The steps the tweet asks its readers to take, in order.
+    pub tasks: Vec<AirdropTask>,
+    /// The dates the tweet announces (snapshot, claim window, TGE, end), resolved to UTC.
+    pub deadlines: Vec<Deadline>,
+    /// The earliest snapshot, claim end or end among `deadlines`, to sort by urgency.
+    pub nextDeadline: Option<DateTime<Utc>>,
+    /// The reward amounts the tweet announces.
+    pub rewards: Vec<Reward>,
+    /// The chains the tweet is about, most confident first.
+    pub chains: Vec<ChainMatch>,
+    /// The risk flags of the tweet's links, combined; empty if no link looks risky.
+    pub riskFlags: Vec<RiskFlag>,
+    /// Id of the cluster of tweets announcing the same opportunity, `None` while unclustered.
+    pub clusterId: Option<String>,
 }
 
 /// `UserProfileScraper` is responsible for automating web browser
@@ -44,6 +115,9 @@ pub struct Airdrop {
 pub struct UserProfileScraper {
     browser: Browser,
     user_data_dir: PathBuf,
+    scraping: ScrapingConfig,
+    login: LoginConfig,
+    backfill: BackfillConfig,
 }
 
 impl UserProfileScraper {
@@ -53,6 +127,10 @@ impl UserProfileScraper {
     /// Chrome browser instance and sets up a user data directory for
     /// persistent Browse data like cookies and local storage.
     ///
+    /// # Arguments
+    /// * `config` - The resolved application configuration. The browser section drives
+    ///   the launch options, the scraping and login sections are kept for later scrapes.
+    ///
     /// # Returns
     /// `Result<Self>`: A `Result` indicating success (`UserProfileScraper`)
     /// or failure (an `anyhow::Error`).
@@ -60,9 +138,9 @@ impl UserProfileScraper {
     /// # Errors
     /// Returns an error if the browser cannot be launched or the user data
     /// directory cannot be created.
-    pub fn new() -> Result<Self> {
-        let run_headless_hardcoded = false; // Hardcoded for development; consider making this configurable.
-        let user_data_dir_path = PathBuf::from("./chrome_scraper_profile");
+    pub fn new(config: &Config) -> Result<Self> {
+        let browser_config = &config.browser;
+        let user_data_dir_path = browser_config.user_data_dir.clone();
 
         if !user_data_dir_path.exists() {
             fs::create_dir_all(&user_data_dir_path)?;
@@ -72,17 +150,26 @@ impl UserProfileScraper {
         }
 
         let mut launch_options_builder = headless_chrome::LaunchOptions::default_builder();
-        launch_options_builder.headless(run_headless_hardcoded);
+        launch_options_builder.headless(browser_config.headless);
         
         // Configure browser options to mimic a typical user.
+        let user_agent_arg = format!("--user-agent={}", browser_config.user_agent);
         launch_options_builder.user_data_dir(Some(user_data_dir_path.clone()));
-        launch_options_builder.args(vec![std::ffi::OsStr::new("--user-agent=Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36")]);
-        launch_options_builder.window_size(Some((1920, 1080)));
-        launch_options_builder.args(vec![std::ffi::OsStr::new("--disable-blink-features=AutomationControlled")]);
+        launch_options_builder.args(vec![
+            std::ffi::OsStr::new(&user_agent_arg),
+            std::ffi::OsStr::new("--disable-blink-features=AutomationControlled"),
+        ]);
+        launch_options_builder.window_size(Some((browser_config.window_width, browser_config.window_height)));
         
         let options = launch_options_builder.build()?;
         let browser = Browser::new(options)?;
-        Ok(Self { browser, user_data_dir: user_data_dir_path })
+        Ok(Self {
+            browser,
+            user_data_dir: user_data_dir_path,
+            scraping: config.scraping.clone(),
+            login: config.login.clone(),
+            backfill: config.backfill.clone(),
+        })
     }
 
     /// Scrapes tweet HTML content from a user's profile page.
@@ -92,20 +179,68 @@ impl UserProfileScraper {
     /// page to load and collect the HTML of tweets. It handles potential
     /// login requirements using the `LoginHandler`.
     ///
+    /// When `scraping.incremental` is enabled and the target carries a watermark
+    /// (`since_tweet_id` / `since_time`), scrolling stops at the first chronological tweet
+    /// that was already collected by an earlier run; that tweet is not collected again.
+    ///
+    /// In backfill mode (`target.until` set) the watermark and `scraping.tweet_limit` are
+    /// ignored: scrolling continues until a chronological tweet older than `until` appears, up
+    /// to `backfill.max_tweets`. Progress is checkpointed to `backfill.checkpoint_dir`, and an
+    /// interrupted backfill resumes below its oldest collected tweet. The checkpoint is left in
+    /// place: the caller clears it once the batch is stored (see `BackfillCheckpoint::clear`).
+    ///
+    /// With `scraping.capture_graphql` enabled, the `UserTweets` / `TweetDetail` GraphQL
+    /// responses loaded while scrolling are captured into the batch as well, and the
+    /// `SearchTimeline` responses of a resumed backfill.
+    ///
+    /// With `scraping.expand_threads` enabled, the collected tweets that look like thread
+    /// starts are expanded afterwards, see `expand_threads`.
+    ///
     /// # Arguments
-    /// * `profile_url` - The URL of the user's profile page to scrape.
-    /// * `author_id` - The ID of the author whose tweets are being scraped.
+    /// * `target` - The profile URL, the author ID the tweets are attributed to, and the
+    ///   optional watermark of the previous run.
     ///
     /// # Returns
-    /// `Result<HashSet<String>>`: A `Result` containing a `HashSet` of unique
-    /// tweet HTML strings if successful, or an `anyhow::Error` on failure.
-    /// The `HashSet` will also include a string in the format "author_id: <id>".
+    /// `ScrapeResult<ScrapedBatch>`: A `Result` containing the `ScrapedBatch` with the
+    /// unique tweet HTML in timeline order if successful, or a `ScrapeError` on failure.
     ///
     /// # Errors
-    /// Returns an error if navigation fails, elements cannot be found,
-    /// or scraping encounters persistent issues.
-    pub fn scrape_user_posts(&self, profile_url: &str, author_id: &str) -> Result<HashSet<String>> {
-        let tab: Arc<Tab> = self.browser.new_tab()?;
+    /// * `ScrapeError::ProfileNotFound`, `ProfileSuspended` or `ProfileProtected` if the
+    ///   profile cannot be scraped at all.
+    /// * `ScrapeError::AccountSuspended`, `LoginRequired`, `LoginFailed` or `LoginChallenge`
+    ///   if the bot account cannot see the profile.
+    /// * `ScrapeError::RateLimited` if the platform throttles the session.
+    /// * `ScrapeError::SelectorNotFound` if no tweet element ever appears on the page.
+    /// * `ScrapeError::Browser` if navigation or the DevTools connection fails.
+    pub fn scrape_user_posts(&self, target: &ScrapeTarget) -> ScrapeResult<ScrapedBatch> {
+        let profile_url = target.profile_url.as_str();
+        let author_id = target.author_id.as_str();
+
+        // A backfill continues from its checkpoint if an earlier attempt was interrupted.
+        let checkpoint = target
+            .until
+            .map(|until| BackfillCheckpoint::new(&self.backfill.checkpoint_dir, target.handle(), until.date_naive()));
+        let mut batch = checkpoint
+            .as_ref()
+            .and_then(BackfillCheckpoint::load)
+            .unwrap_or_else(|| ScrapedBatch::new(author_id, profile_url));
+        let resume_from = batch.oldest.clone();
+        // The pool reuses the browser for many profiles, so the tab is closed on every exit.
+        let tab = ClosingTab::new(self.browser.new_tab()?, "profile");
+
+        // Keep the GraphQL timeline JSON the page loads while scrolling; the HTML collected
+        // below remains the fallback for tweets it does not cover.
+        let graphql_capture = if self.scraping.capture_graphql {
+            match GraphqlCapture::attach(&tab) {
+                Ok(capture) => Some(capture),
+                Err(e) => {
+                    println!("Warning: Failed to enable GraphQL capture, collecting HTML only: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
         let tweet_selector = "article[data-testid='tweet']";
 
         // Attempt to load and set cookies from a previously saved file.
@@ -164,10 +299,10 @@ impl UserProfileScraper {
         tab.navigate_to(profile_url)?;
         tab.wait_until_navigated()?;
         println!("Page loaded. Waiting a bit for dynamic content...");
-        thread::sleep(Duration::from_secs(5));
+        thread::sleep(self.scraping.page_load_settle());
+        check_page_state(&tab, profile_url)?;
 
         // Determine if login is needed and attempt login using LoginHandler.
-        let should_attempt_login_hardcoded = true; // Still hardcoded, but now passed to LoginHandler.
         let mut login_needed = true;
 
         let initial_login_trigger_sel = "a[data-testid='login']"; 
@@ -184,7 +319,7 @@ impl UserProfileScraper {
             println!("Login button found. Login is likely required.");
         }
 
-        if login_needed && should_attempt_login_hardcoded {
+        if login_needed && self.login.enabled {
             println!("Login required. Attempting login process...");
             // Define selectors for the login flow. These are specific to the target website.
             let username_sel_in_form = "input[name='text']"; 
@@ -204,46 +339,70 @@ impl UserProfileScraper {
                 final_login_button_sel_in_form,
                 success_url_part_after_login, 
                 success_element_sel_after_login,
+                &self.login,
                 &self.user_data_dir, // Pass user_data_dir for cookie saving
             )?;
+            login_needed = false;
             println!("Login process completed. Re-navigating to profile page to ensure correct state...");
             tab.navigate_to(profile_url)?;
             tab.wait_until_navigated()?;
-            thread::sleep(Duration::from_secs(5));
+            thread::sleep(self.scraping.page_load_settle());
+            check_page_state(&tab, profile_url)?;
         } else if !login_needed {
             println!("Skipping login attempt as session appears active.");
             if tab.get_url() != profile_url {
                 println!("Not on profile URL, navigating to {}", profile_url);
                 tab.navigate_to(profile_url)?;
                 tab.wait_until_navigated()?;
-                thread::sleep(Duration::from_secs(5));
+                thread::sleep(self.scraping.page_load_settle());
             }
         } else {
-            println!("Login is required but 'should_attempt_login_hardcoded' is false. Scraping may fail or be limited.");
+            println!("Login is required but login is disabled in the configuration. Scraping may fail or be limited.");
         }
 
-        let mut collected_html_set: HashSet<String> = HashSet::new();
-        // Add author_id to the set for later processing/identification.
-        collected_html_set.insert(format!("author_id: {}", author_id));
+        // A resumed backfill jumps below its oldest collected tweet with a date-bounded search
+        // instead of scrolling through the whole profile again.
+        if let Some(resume_url) = resume_from.as_ref().and_then(|oldest| backfill_resume_url(target.handle(), oldest)) {
+            println!("Resuming backfill below tweet {:?}: {}", resume_from.as_ref().map(|oldest| &oldest.tweet_id), resume_url);
+            tab.navigate_to(&resume_url)?;
+            tab.wait_until_navigated()?;
+            thread::sleep(self.scraping.page_load_settle());
+            check_page_state(&tab, profile_url)?;
+        }
 
-        let mut last_known_good_item_height: f64 = 250.0; // Initial estimate for tweet height
-        let settle_time_after_scroll = Duration::from_secs(3);
-        let settle_time_after_recovery_scroll = Duration::from_secs(5);
-        const MAX_CONSECUTIVE_FAILED_CYCLES: usize = 3; 
+        let mut last_known_good_item_height: f64 = self.scraping.initial_item_height; // Initial estimate for tweet height
+        let settle_time_after_scroll = self.scraping.settle_after_scroll();
+        let settle_time_after_recovery_scroll = self.scraping.settle_after_recovery_scroll();
+        let max_consecutive_failed_cycles = self.scraping.max_consecutive_failed_cycles;
         let mut consecutive_failed_cycles = 0;
         let mut first_scroll_performed = false;
-        let mut total_elements_found_count: usize = 0;
         let mut reached_tweet_limit = false;
-        const TWEET_LIMIT: usize = 50; // Max number of tweets to collect.
+        let mut reached_watermark = false;
+        let mut reached_until = false;
+        let backfill = target.until.is_some();
+        // Max number of tweets to collect.
+        let tweet_limit = if backfill { self.backfill.max_tweets } else { self.scraping.tweet_limit };
+        let incremental = !backfill
+            && self.scraping.incremental
+            && (target.since_tweet_id.is_some() || target.since_time.is_some());
+        let mut checkpointed_len = batch.len();
+        let mut overlap_ids: HashSet<String> = HashSet::new();
 
 
-        println!("Starting iterative scrolling and HTML collection (limit {} tweets)...", TWEET_LIMIT);
+        println!("Starting iterative scrolling and HTML collection (limit {} tweets)...", tweet_limit);
+        if incremental {
+            println!("Incremental scrape: stopping at tweet {:?} / {:?}.", target.since_tweet_id, target.since_time);
+        }
+        if let Some(until) = target.until {
+            println!("Backfill: scrolling back to {}.", until);
+        }
 
-        loop {
+        let stop_reason = loop {
             if reached_tweet_limit {
-                break;
+                break StopReason::TweetLimit;
             }
 
+            batch.stats.scroll_cycles += 1;
             let mut new_items_added_this_cycle = 0;
             let mut height_of_last_new_item_this_cycle = 0.0;
 
@@ -252,6 +411,26 @@ impl UserProfileScraper {
             
             let elements_in_view = match elements_in_view_result {
                 Ok(elements) => elements,
+                Err(e) if e.downcast_ref::<NoElementFound>().is_some() && !batch.is_empty() => {
+                    // Tweets were collected earlier; an empty view is treated like a cycle with nothing new.
+                    Vec::new()
+                }
+                Err(e) if e.downcast_ref::<NoElementFound>().is_some() => {
+                    println!("No tweet elements found on the page. Checking page state...");
+                    check_page_state(&tab, profile_url)?;
+                    if tab.find_element(EMPTY_STATE_SELECTOR).is_ok() {
+                        println!("Profile has no posts (empty state shown).");
+                        batch.finish(StopReason::EmptyProfile);
+                        return Ok(batch);
+                    }
+                    if login_needed {
+                        return Err(ScrapeError::LoginRequired { url: profile_url.to_string() });
+                    }
+                    return Err(ScrapeError::SelectorNotFound {
+                        selector: tweet_selector.to_string(),
+                        url: tab.get_url(),
+                    });
+                }
                 Err(e) => {
                     println!("Error finding tweet elements during scan: {}. Terminating.", e);
                     return Err(e.into()); // Return error if find_elements fails
@@ -259,34 +438,74 @@ impl UserProfileScraper {
             };
             
             println!("Found {} potential tweet elements in view.", elements_in_view.len());
-            total_elements_found_count += elements_in_view.len();
+            batch.stats.elements_seen += elements_in_view.len();
 
             // Iterate through found elements and collect their HTML.
             for el_arc in elements_in_view.iter() {
-                if collected_html_set.len() >= TWEET_LIMIT + 1 { // +1 for the author_id string
-                    println!("Reached the limit of {} tweet HTMLs. Stopping collection.", TWEET_LIMIT);
+                if batch.len() >= tweet_limit {
+                    println!("Reached the limit of {} tweet HTMLs. Stopping collection.", tweet_limit);
                     reached_tweet_limit = true;
                     break; 
                 }
                 match el_arc.get_content() {
                     Ok(html_content) => {
-                        if collected_html_set.insert(html_content) {
+                        let entry = TimelineEntry::from_html(&html_content)?;
+                        if incremental && target.is_seen(&entry) {
+                            println!("Reached tweet {:?}, already collected by an earlier run. Stopping collection.", entry.tweet_id);
+                            reached_watermark = true;
+                            break;
+                        }
+                        if target.is_past_until(&entry) {
+                            println!("Reached tweet {:?} from {:?}, older than the backfill date. Stopping collection.", entry.tweet_id, entry.created_at);
+                            reached_until = true;
+                            break;
+                        }
+                        // After resuming, the first results overlap with the checkpoint. They count as
+                        // progress, so the overlap is scrolled past instead of ending the scroll.
+                        if let Some(oldest) = &resume_from
+                            && entry.is_chronological()
+                            && let Some(tweet_id) = &entry.tweet_id
+                            && compare_tweet_ids(tweet_id, &oldest.tweet_id) != Ordering::Less
+                        {
+                            if overlap_ids.insert(tweet_id.clone()) {
+                                new_items_added_this_cycle += 1;
+                            }
+                            continue;
+                        }
+                        if batch.push_html(html_content) {
+                            batch.observe(&entry);
                             new_items_added_this_cycle += 1;
-                            if let Ok(model) = el_arc.get_box_model() {
-                                if model.height > 0.0 {
-                                    height_of_last_new_item_this_cycle = model.height;
-                                }
+                            if let Ok(model) = el_arc.get_box_model()
+                                && model.height > 0.0
+                            {
+                                height_of_last_new_item_this_cycle = model.height;
                             }
                         }
                     }
                     Err(e) => {
                         println!("Warning: Failed to get HTML content for an element: {}", e);
+                        batch.stats.read_failures += 1;
                     }
                 }
             }
 
             if reached_tweet_limit { 
-                break;
+                break StopReason::TweetLimit;
+            }
+            if reached_watermark {
+                break StopReason::ReachedWatermark;
+            }
+            if reached_until {
+                break StopReason::ReachedUntil;
+            }
+
+            if let Some(checkpoint) = &checkpoint
+                && batch.len() >= checkpointed_len + self.backfill.checkpoint_every
+            {
+                match checkpoint.save(&batch) {
+                    Ok(()) => checkpointed_len = batch.len(),
+                    Err(e) => println!("Warning: Failed to save the backfill checkpoint: {}", e),
+                }
             }
 
             // Adjust scrolling behavior based on whether new content was found.
@@ -300,10 +519,11 @@ impl UserProfileScraper {
             } else {
                 println!("No new item HTML added in this cycle from the current view.");
                 consecutive_failed_cycles += 1;
-                println!("Consecutive failed cycles: {}/{}", consecutive_failed_cycles, MAX_CONSECUTIVE_FAILED_CYCLES);
-                if consecutive_failed_cycles >= MAX_CONSECUTIVE_FAILED_CYCLES {
+                batch.stats.failed_cycles += 1;
+                println!("Consecutive failed cycles: {}/{}", consecutive_failed_cycles, max_consecutive_failed_cycles);
+                if consecutive_failed_cycles >= max_consecutive_failed_cycles {
                     println!("Max consecutive failed cycles reached. Assuming end of content or no more new tweets visible.");
-                    break;
+                    break StopReason::NoNewContent;
                 }
             }
 
@@ -328,10 +548,10 @@ impl UserProfileScraper {
             if scroll_amount <= 10.0 { 
                 println!("Scroll amount {:.2} is too small. Considering this a failed cycle.", scroll_amount);
                 consecutive_failed_cycles +=1; 
-                println!("Incrementing failed cycles due to small scroll. Now {}/{}", consecutive_failed_cycles, MAX_CONSECUTIVE_FAILED_CYCLES);
-                if consecutive_failed_cycles >= MAX_CONSECUTIVE_FAILED_CYCLES {
+                println!("Incrementing failed cycles due to small scroll. Now {}/{}", consecutive_failed_cycles, max_consecutive_failed_cycles);
+                if consecutive_failed_cycles >= max_consecutive_failed_cycles {
                     println!("Max consecutive failed cycles reached after small scroll. Assuming end of content.");
-                    break;
+                    break StopReason::NoNewContent;
                 }
                 if !reached_tweet_limit { 
                     // Attempt a final, larger recovery scroll if limit not yet reached.
@@ -341,7 +561,7 @@ impl UserProfileScraper {
                     thread::sleep(settle_time_after_recovery_scroll);
                     continue; 
                 } else {
-                    break; 
+                    break StopReason::TweetLimit; 
                 }
             }
 
@@ -349,20 +569,29 @@ impl UserProfileScraper {
             tab.evaluate(&format!("window.scrollBy(0, {});", scroll_amount), false)?;
             println!("Scrolled by {:.2}. Waiting for content to settle...", scroll_amount);
             thread::sleep(settle_time_after_scroll);
+        };
+        if self.scraping.expand_threads {
+            self.expand_threads(&mut batch, target.handle());
+        }
+        if let Some(capture) = &graphql_capture {
+            let captured = capture.drain_into(&mut batch);
+            println!("Captured {} GraphQL timeline responses.", captured);
         }
+        batch.finish(stop_reason);
 
         println!("\n--- Scraping Summary for author_id: {} ---", author_id);
-        println!("Total tweet selector elements encountered (raw count): {}", total_elements_found_count);
+        println!("Total tweet selector elements encountered (raw count): {}", batch.stats.elements_seen);
         
-        let actual_tweet_html_count = collected_html_set.len().saturating_sub(1); // Subtract 1 for the author_id string.
+        let actual_tweet_html_count = batch.len();
 
-        println!("Finished scrolling and HTML collection. Target tweet limit: {}", TWEET_LIMIT);
+        println!("Finished scrolling and HTML collection. Target tweet limit: {}", tweet_limit);
         println!("Total unique tweet HTMLs collected: {}", actual_tweet_html_count);
-        println!("Total items in HashSet (including author_id string): {}", collected_html_set.len());
+        println!("Scroll cycles: {}, duplicates skipped: {}, stop reason: {:?}", batch.stats.scroll_cycles, batch.stats.duplicates_skipped, stop_reason);
 
 
         // Capture a screenshot if no actual tweet HTML content was collected.
-        if actual_tweet_html_count == 0 {
+        // An incremental scrape that found nothing new is expected to be empty.
+        if actual_tweet_html_count == 0 && stop_reason != StopReason::ReachedWatermark {
             println!("No actual tweet HTML content was collected for {}.", author_id);
             if let Ok(data) = tab.capture_screenshot(Page::CaptureScreenshotFormatOption::Png, None, None, true) {
                 let screenshot_path = format!("{}_no_html_collected_debug.png", author_id);
@@ -374,6 +603,201 @@ impl UserProfileScraper {
             }
         }
         
-        Ok(collected_html_set)
+        Ok(batch)
     }
-}
\ No newline at end of file
+}
+
+impl UserProfileScraper {
+    /// Opens the status page of every collected tweet that looks like a thread start, up to
+    /// `scraping.max_thread_expansions`, and adds the author's consecutive self-replies to
+    /// `batch.threads`. Pinned tweets, reposts and already expanded tweets are skipped.
+    ///
+    /// Thread expansion only enriches the batch: a failure is logged and ends the
+    /// expansion, keeping the threads collected so far.
+    fn expand_threads(&self, batch: &mut ScrapedBatch, handle: &str) {
+        let mut roots = Vec::new();
+        for tweet in &batch.tweets {
+            let document = scraper::Html::parse_fragment(&tweet.html);
+            let Ok(entry) = TimelineEntry::from_document(&document) else {
+                continue;
+            };
+            let Some(tweet_id) = entry.tweet_id.filter(|_| entry.social_context.is_none()) else {
+                continue;
+            };
+            if batch.threads.iter().any(|thread| thread.root_tweet_id == tweet_id) {
+                continue;
+            }
+            if looks_like_thread_start(&document).unwrap_or(false) {
+                roots.push(tweet_id);
+            }
+            if roots.len() >= self.scraping.max_thread_expansions {
+                break;
+            }
+        }
+        if roots.is_empty() {
+            return;
+        }
+
+        println!("Expanding {} possible threads of @{}...", roots.len(), handle);
+        let tab = match self.browser.new_tab() {
+            Ok(tab) => ClosingTab::new(tab, "thread expansion"),
+            Err(e) => {
+                println!("Warning: Failed to open a tab for thread expansion: {}", e);
+                return;
+            }
+        };
+        let graphql_capture = if self.scraping.capture_graphql { GraphqlCapture::attach(&tab).ok() } else { None };
+        for root_tweet_id in roots {
+            match self.collect_thread(&tab, handle, &root_tweet_id) {
+                Ok(collector) => {
+                    let thread = collector.into_thread();
+                    println!("Thread {}: {} self-replies.", root_tweet_id, thread.replies.len());
+                    if !thread.replies.is_empty() {
+                        batch.threads.push(thread);
+                    }
+                }
+                Err(e) => {
+                    println!("Warning: Failed to expand thread {}: {}. Skipping the remaining threads.", root_tweet_id, e);
+                    break;
+                }
+            }
+        }
+        if let Some(capture) = &graphql_capture {
+            capture.drain_into(batch);
+        }
+    }
+
+    /// Opens the status page of `root_tweet_id` in `tab` and scrolls it until the thread of
+    /// self-replies ends, reaches `scraping.max_thread_length` or nothing new loads.
+    ///
+    /// # Errors
+    /// * `ScrapeError::RateLimited`, `AccountSuspended` or another page state error reported
+    ///   by the status page.
+    /// * `ScrapeError::Browser` if navigation or the DevTools connection fails.
+    fn collect_thread(&self, tab: &Tab, handle: &str, root_tweet_id: &str) -> ScrapeResult<ThreadCollector> {
+        let status_url = format!("https://x.com/{}/status/{}", handle, root_tweet_id);
+        tab.navigate_to(&status_url)?;
+        tab.wait_until_navigated()?;
+        thread::sleep(self.scraping.page_load_settle());
+        check_page_state(tab, &status_url)?;
+
+        let mut collector = ThreadCollector::new(handle, root_tweet_id, self.scraping.max_thread_length);
+        let mut consecutive_failed_cycles = 0;
+        while !collector.is_finished() && consecutive_failed_cycles < self.scraping.max_consecutive_failed_cycles {
+            let mut added = 0;
+            for element in tab.find_elements("article[data-testid='tweet']").unwrap_or_default() {
+                if let Ok(html) = element.get_content()
+                    && collector.observe(html)?
+                {
+                    added += 1;
+                }
+                if collector.is_finished() {
+                    break;
+                }
+            }
+            consecutive_failed_cycles = if added > 0 { 0 } else { consecutive_failed_cycles + 1 };
+            tab.evaluate("window.scrollBy(0, window.innerHeight * 0.8);", false)?;
+            thread::sleep(self.scraping.settle_after_scroll());
+        }
+        Ok(collector)
+    }
+}
+
+impl Scraper for UserProfileScraper {
+    /// Scrapes the target's profile page with `scrape_user_posts`.
+    fn scrape(&self, target: &ScrapeTarget) -> ScrapeResult<ScrapedBatch> {
+        self.scrape_user_posts(target)
+    }
+
+    /// Returns `true` if the browser process still answers DevTools requests.
+    fn is_alive(&self) -> bool {
+        self.browser.get_version().is_ok()
+    }
+}
+
+/// A tab that is closed when dropped, so an early return or a panic caught by the pool does not
+/// leave it open, together with its GraphQL response listener, in a reused browser.
+struct ClosingTab {
+    tab: Arc<Tab>,
+    purpose: &'static str,
+}
+
+impl ClosingTab {
+    fn new(tab: Arc<Tab>, purpose: &'static str) -> Self {
+        Self { tab, purpose }
+    }
+}
+
+impl std::ops::Deref for ClosingTab {
+    type Target = Arc<Tab>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tab
+    }
+}
+
+impl Drop for ClosingTab {
+    fn drop(&mut self) {
+        if let Err(e) = self.tab.close(true) {
+            println!("Warning: Failed to close the {} tab: {}", self.purpose, e);
+        }
+    }
+}
+
+/// Selector for the placeholder X renders instead of a timeline (missing, suspended,
+/// protected or empty profiles).
+const EMPTY_STATE_SELECTOR: &str = "div[data-testid='emptyState']";
+
+/// Inspects the rendered page for states that make scraping the profile impossible.
+///
+/// # Arguments
+/// * `tab` - The tab currently showing the profile page.
+/// * `profile_url` - The profile URL, used in the returned errors.
+///
+/// # Returns
+/// `ScrapeResult<()>`: `Ok(())` if nothing prevents scraping.
+///
+/// # Errors
+/// Returns the matching `ScrapeError` if the bot account has been locked, the platform
+/// reports a rate limit, or the profile does not exist, is suspended or is protected.
+fn check_page_state(tab: &Tab, profile_url: &str) -> ScrapeResult<()> {
+    let current_url = tab.get_url();
+    if current_url.contains("/account/access") || current_url.contains("/account/suspended") {
+        return Err(ScrapeError::AccountSuspended { url: current_url });
+    }
+
+    let body_text = tab
+        .find_element("body")
+        .and_then(|body| body.get_inner_text())
+        .unwrap_or_default();
+    if body_text.contains("Rate limit exceeded") {
+        return Err(ScrapeError::RateLimited { retry_after: None });
+    }
+
+    if let Ok(empty_state) = tab.find_element(EMPTY_STATE_SELECTOR) {
+        let text = empty_state.get_inner_text().unwrap_or_default().replace('\u{2019}', "'");
+        if text.contains("This account doesn't exist") {
+            return Err(ScrapeError::ProfileNotFound { url: profile_url.to_string() });
+        }
+        if text.contains("Account suspended") {
+            return Err(ScrapeError::ProfileSuspended { url: profile_url.to_string() });
+        }
+        if text.contains("These posts are protected") {
+            return Err(ScrapeError::ProfileProtected { url: profile_url.to_string() });
+        }
+    }
+    Ok(())
+}
+
+/// Builds the URL that continues a backfill below `oldest`: a latest-first search of the
+/// profile's own posts up to the day after the oldest collected tweet (`until:` is exclusive).
+///
+/// # Returns
+/// `Option<String>`: `None` if the creation time of `oldest` is unknown.
+fn backfill_resume_url(handle: &str, oldest: &Watermark) -> Option<String> {
+    let until = oldest.created_at?.date_naive().succ_opt()?;
+    Some(format!(
+        "https://x.com/search?q=from%3A{}%20-filter%3Areplies%20until%3A{}&src=typed_query&f=live",
+        handle, until
+    ))
+}
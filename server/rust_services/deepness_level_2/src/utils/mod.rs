@@ -0,0 +1,72 @@
+//! General utility functions shared by the scraper, the processors and the repositories.
+
+use crate::errors::{ScrapeError, ScrapeResult};
+use scraper::Selector;
+use std::cmp::Ordering;
+
+/// Compares two tweet ids chronologically.
+///
+/// Tweet ids are snowflakes: numeric strings that grow over time. Comparing them by length
+/// first and then lexically orders them correctly without parsing, even beyond `u64`.
+pub fn compare_tweet_ids(a: &str, b: &str) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Returns the handle of a profile URL, i.e. its last path segment
+/// (`solana` for `https://x.com/solana`).
+pub fn handle_from_url(profile_url: &str) -> &str {
+    profile_url.trim_end_matches('/').rsplit('/').next().unwrap_or_default()
+}
+
+/// Returns the handle of a profile link such as `/solana`, or `None` for any other link.
+pub fn handle_from_href(href: &str) -> Option<String> {
+    let handle = href.strip_prefix('/')?;
+    (!handle.is_empty() && handle.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')).then(|| handle.to_string())
+}
+
+/// Returns `true` if `host` is `domain` or one of its subdomains (`app.jup.ag` for `jup.ag`,
+/// but not `notjup.ag`).
+pub fn is_host(host: &str, domain: &str) -> bool {
+    host.strip_suffix(domain).is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('.'))
+}
+
+/// Returns the tweet id of a status link such as `/alice/status/123`, or `None` if the link
+/// has no numeric status id.
+pub fn tweet_id_from_href(href: &str) -> Option<String> {
+    let id = href.split("/status/").nth(1)?.split(['/', '?']).next()?;
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+}
+
+/// Splits a tweet text into sentences at line breaks and at `.`, `!` and `?` followed by
+/// whitespace, so URLs and decimals stay whole. Sentences are trimmed; those without a letter
+/// or digit are dropped.
+pub fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    for line in text.lines() {
+        let mut start = 0;
+        for (index, c) in line.char_indices() {
+            let ends_sentence = matches!(c, '.' | '!' | '?')
+                && line[index + c.len_utf8()..].starts_with(char::is_whitespace);
+            if ends_sentence {
+                sentences.push(&line[start..=index]);
+                start = index + 1;
+            }
+        }
+        sentences.push(&line[start..]);
+    }
+    sentences.into_iter().map(str::trim).filter(|sentence| sentence.chars().any(char::is_alphanumeric)).collect()
+}
+
+/// Parses a CSS selector, mapping a failure to `ScrapeError::InvalidSelector`.
+///
+/// # Arguments
+/// * `selector` - The CSS selector to parse.
+///
+/// # Returns
+/// `ScrapeResult<Selector>`: The parsed selector, or an error naming the invalid selector.
+pub fn parse_selector(selector: &str) -> ScrapeResult<Selector> {
+    Selector::parse(selector).map_err(|e| ScrapeError::InvalidSelector {
+        selector: selector.to_string(),
+        reason: e.to_string(),
+    })
+}